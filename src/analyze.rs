@@ -1,107 +1,256 @@
 use std::collections::HashMap;
 
 use crate::{
-    Block, BlockDetails, BlockType, Data, HttpType, LinqSyntax, QueryType, ReturnData, Table,
-    Variable, WhereClause,
+    lexer::tokenize, route::parse_route, Aggregate, AggregateKind, Assignment, Block, BlockDetails,
+    BlockType, Class, Data, Diagnostic, DiagnosticCode, Expression, HttpType, Join, JoinKey,
+    JoinKind, LinqOperator, LinqSyntax, Member, MemberKind, Model, OperatorKind, OrderKey,
+    ProjectedField, Projection, Property, QueryType, Reference, ReturnData, Route, Severity,
+    SortDirection, Table, Token, TokenKind, Variable, WhereClause,
 };
 
+const MODIFIERS: &[&str] = &[
+    "public",
+    "private",
+    "protected",
+    "internal",
+    "static",
+    "abstract",
+    "sealed",
+    "partial",
+    "readonly",
+    "async",
+    "override",
+    "virtual",
+    "extern",
+    "unsafe",
+];
+
+/// Keywords that start a member other than a property.
+const MEMBER_KEYWORDS: &[&str] = &[
+    "class",
+    "struct",
+    "record",
+    "interface",
+    "enum",
+    "event",
+    "delegate",
+    "operator",
+    "this",
+];
+
+const PARAMETER_MODIFIERS: &[&str] = &["ref", "out", "in", "params", "this"];
+
+const QUERY_KEYWORDS: &[&str] = &["from", "join", "where", "orderby", "select"];
+
+/// Operators that run a query and return its rows, or one of them.
+const TERMINAL_OPERATORS: &[&str] = &[
+    "ToList",
+    "ToArray",
+    "ToDictionary",
+    "ToHashSet",
+    "AsEnumerable",
+    "First",
+    "FirstOrDefault",
+    "Single",
+    "SingleOrDefault",
+    "Last",
+    "LastOrDefault",
+];
+
+const WRITE_OPERATIONS: &[&str] = &[
+    "Add",
+    "AddAsync",
+    "AddRange",
+    "Remove",
+    "RemoveRange",
+    "SaveChanges",
+    "SaveChangesAsync",
+];
+
 pub fn analyze_lines(mut data: Data) -> Data {
-    data.blocks = get_blocks(&data);
-    data = get_class_name(data);
+    data.tokens = tokenize(&data.lines);
+    (data.lines, data.line_numbers) = split_brace_lines(&data);
+    data.tokens = tokenize(&data.lines);
+    data = get_blocks(data);
+    data = get_classes(data);
+    data = get_models(data);
     data = set_block_types(data);
 
     data = attach_block_details(&data);
     data = get_additional_select_blocks(&data);
     data = attach_select_block_details(&data);
+    data = get_write_blocks(&data);
 
     // println!("blocks: {:?}", data.blocks);
 
     data
 }
 
-fn get_block_boundry(line: &String) -> Option<char> {
-    if !line.contains("{") && !line.contains("}") {
-        return None;
+/// A diagnostic for a line of `data.lines`, reported with the 1-based line
+/// number it had in the source file.
+fn diagnostic(
+    data: &Data,
+    code: DiagnosticCode,
+    severity: Severity,
+    line: Option<u32>,
+    message: &str,
+) -> Diagnostic {
+    Diagnostic {
+        code,
+        severity,
+        line: line.map(|line| data.line_numbers.get(line as usize).unwrap_or(&line) + 1),
+        message: message.to_string(),
     }
+}
 
-    let first_char = line.clone().replace(" ", "").chars().nth(0).unwrap();
+/// The code tokens starting on a line, comments and preprocessor directives
+/// are left out.
+fn line_tokens(data: &Data, line: usize) -> Vec<&Token> {
+    let line = line as u32;
+    let start = data
+        .tokens
+        .partition_point(|token| token.span.start_line < line);
+
+    data.tokens[start..]
+        .iter()
+        .take_while(|token| token.span.start_line == line)
+        .filter(|token| !matches!(token.kind, TokenKind::Comment | TokenKind::Directive))
+        .collect()
+}
 
-    if first_char == '{' || first_char == '}' {
-        return Some(first_char);
-    }
+/// The code tokens starting on the lines `first` to `last`.
+fn lines_tokens(data: &Data, first: u32, last: u32) -> Vec<&Token> {
+    (first..=last)
+        .flat_map(|line| line_tokens(data, line as usize))
+        .collect()
+}
 
-    None
+/// The first line of the declaration above the opening brace of a block. A
+/// parameter list split over several lines starts where its `(` is opened.
+pub(crate) fn declaration_start(data: &Data, block: &Block) -> u32 {
+    let mut first = block.start.saturating_sub(1);
+    let mut depth = 0;
+
+    loop {
+        for token in line_tokens(data, first as usize) {
+            if is_punctuation(token, "(") {
+                depth += 1;
+            } else if is_punctuation(token, ")") {
+                depth -= 1;
+            }
+        }
+
+        if depth >= 0 || first == 0 {
+            return first;
+        }
+
+        let ends_statement = line_tokens(data, first as usize - 1)
+            .last()
+            .is_some_and(|token| ["{", "}", ";"].iter().any(|end| is_punctuation(token, end)));
+
+        if ends_statement {
+            return first;
+        }
+
+        first -= 1;
+    }
 }
 
-fn get_correct_index(blocks: &Vec<Block>) -> usize {
-    let mut index = blocks.len() - 1;
+/// The tokens of the declaration above the opening brace of a block,
+/// attributes written in front of the declaration are skipped.
+fn header_tokens<'a>(data: &'a Data, block: &Block) -> Vec<&'a Token> {
+    if block.start == 0 {
+        return vec![];
+    }
 
-    while blocks[index].end.is_some() {
-        index -= 1;
+    let tokens = lines_tokens(data, declaration_start(data, block), block.start - 1);
+    let mut start = 0;
+
+    while tokens
+        .get(start)
+        .is_some_and(|token| is_punctuation(token, "["))
+    {
+        match matching_bracket(&tokens, start) {
+            Some(end) => start = end + 1,
+            None => break,
+        }
     }
 
-    index
+    tokens[start..].to_vec()
 }
 
-fn get_blocks(data: &Data) -> Option<Vec<Block>> {
-    let mut blocks = Vec::new();
+/// The source text from the start of `first` up to the end of `last`.
+fn source_text(data: &Data, first: &Token, last: &Token) -> String {
+    let start = &first.span;
+    let end = &last.span;
 
-    let mut open_count = 0;
+    if start.start_line == end.end_line {
+        return data.lines[start.start_line as usize]
+            [start.start_column as usize..end.end_column as usize]
+            .to_string();
+    }
 
-    for (index, line) in data.lines.iter().enumerate() {
-        let boundry = get_block_boundry(line);
-
-        match boundry {
-            Some('{') => {
-                open_count += 1;
-                blocks.push(Block {
-                    start: index as u32,
-                    end: None,
-                    block_type: BlockType::Unknown,
-                    details: None,
-                });
-            }
-            Some('}') => {
-                open_count -= 1;
-                let correct_index = get_correct_index(&blocks);
-                blocks[correct_index].end = Some(index as u32);
-            }
-            _ => (),
-        }
+    let mut text = data.lines[start.start_line as usize][start.start_column as usize..].to_string();
+
+    for line in start.start_line + 1..end.end_line {
+        text.push('\n');
+        text.push_str(&data.lines[line as usize]);
     }
 
-    assert!(open_count == 0, "openCount is not 0");
+    text.push('\n');
+    text.push_str(&data.lines[end.end_line as usize][..end.end_column as usize]);
 
-    Some(blocks)
+    text
 }
 
-fn find_class_block_index(data: &Data) -> Option<usize> {
-    if let Some(blocks) = &data.blocks {
-        for (index, block) in blocks.iter().enumerate() {
-            let previous_line = &data.lines[block.start as usize - 1];
-            let no_spaces = previous_line.replace(" ", "");
-            let keywords = vec!["class", "public", "private", "protected"];
+/// The source text of a run of tokens, empty when there are none.
+fn tokens_text(data: &Data, tokens: &[&Token]) -> String {
+    match tokens {
+        [] => String::new(),
+        [first, .., last] => source_text(data, first, last),
+        [only] => source_text(data, only, only),
+    }
+}
 
-            let mut current_keyword: Option<&str> = None;
+fn is_punctuation(token: &Token, text: &str) -> bool {
+    token.kind == TokenKind::Punctuation && token.text == text
+}
 
-            for keyword in keywords {
-                if no_spaces[0..keyword.len()] == *keyword {
-                    current_keyword = Some(keyword);
-                    break;
-                }
-            }
+fn is_keyword(token: &Token, text: &str) -> bool {
+    token.kind == TokenKind::Keyword && token.text == text
+}
 
-            if current_keyword == Some("class") {
-                return Some(index);
-            }
+fn is_identifier(token: &Token, text: &str) -> bool {
+    token.kind == TokenKind::Identifier && token.text == text
+}
 
-            if current_keyword.is_some() {
-                let keyword_len = current_keyword.unwrap().len();
+fn is_string(token: &Token) -> bool {
+    matches!(
+        token.kind,
+        TokenKind::String | TokenKind::VerbatimString | TokenKind::InterpolatedString
+    )
+}
 
-                let is_class = no_spaces[keyword_len..keyword_len + 5] == *"class";
-                if is_class {
-                    return Some(index);
-                }
+/// Index of the token closing the bracket opened at `open`.
+fn matching_bracket(tokens: &[&Token], open: usize) -> Option<usize> {
+    let (opening, closing) = match tokens.get(open)?.text.as_str() {
+        "(" => ("(", ")"),
+        "[" => ("[", "]"),
+        "{" => ("{", "}"),
+        "<" => ("<", ">"),
+        _ => return None,
+    };
+
+    let mut depth = 0;
+
+    for (index, token) in tokens.iter().enumerate().skip(open) {
+        if is_punctuation(token, opening) {
+            depth += 1;
+        } else if is_punctuation(token, closing) {
+            depth -= 1;
+
+            if depth == 0 {
+                return Some(index);
             }
         }
     }
@@ -109,610 +258,2913 @@ fn find_class_block_index(data: &Data) -> Option<usize> {
     None
 }
 
-fn get_class_name(mut data: Data) -> Data {
-    let class_block_index = find_class_block_index(&data);
-
-    if class_block_index.is_none() || data.blocks.is_none() {
-        return data;
-    }
-
-    let class_block_index = class_block_index.unwrap();
+/// Member accesses like `ut.UserOid`, the start of every access chain split
+/// into the shortcut and the property read from it.
+fn member_accesses(tokens: &[&Token]) -> (Vec<String>, Vec<String>) {
+    let mut shortcuts = vec![];
+    let mut properties = vec![];
 
-    if let Some(mut blocks) = data.blocks {
-        blocks[class_block_index].block_type = BlockType::Class;
-        let no_spaces = data.lines[blocks[class_block_index].start as usize - 1].replace(" ", "");
-        let start = no_spaces.find("class").unwrap() + 5;
-        let end = no_spaces.find(":").unwrap();
+    for index in 0..tokens.len().saturating_sub(2) {
+        let starts_chain = index == 0 || !is_punctuation(tokens[index - 1], ".");
 
-        data.class_name = Some(no_spaces[start..end].to_string());
-
-        return Data {
-            lines: data.lines,
-            blocks: Some(blocks),
-            class_name: data.class_name,
-        };
+        if starts_chain
+            && tokens[index].kind == TokenKind::Identifier
+            && is_punctuation(tokens[index + 1], ".")
+            && tokens[index + 2].kind == TokenKind::Identifier
+        {
+            shortcuts.push(tokens[index].text.clone());
+            properties.push(tokens[index + 2].text.clone());
+        }
     }
 
-    data
+    (shortcuts, properties)
 }
 
-fn get_blocktype_from_string(str: &str) -> BlockType {
-    match str {
-        "namespace" => BlockType::Namespace,
-        "class" => BlockType::Class,
-        "method" => BlockType::Method,
-        "constructor" => BlockType::Constructor,
-        "context" => BlockType::Context,
-        "variable" => BlockType::Variable,
-        "if" => BlockType::If,
-        "select" => BlockType::Select,
-        _ => BlockType::Unknown,
-    }
-}
-
-fn determine_block_type(
-    block: &Block,
-    lines: &Vec<String>,
-    class_name: &Option<String>,
-) -> BlockType {
-    let previous_line = &lines[block.start as usize - 1];
-    let no_spaces = previous_line.replace(" ", "");
-
-    let keywords = vec![
-        "namespace",
-        "class",
-        "public",
-        "private",
-        "protected",
-        "using",
-        "var",
-        "select",
-        "if",
-        "else",
-    ];
-
-    let mut current_keyword = "unknown";
-
-    for keyword in keywords {
-        if no_spaces.len() < keyword.len() {
+fn skip_modifiers(tokens: &[&Token]) -> usize {
+    tokens
+        .iter()
+        .take_while(|token| MODIFIERS.contains(&token.text.as_str()))
+        .count()
+}
+
+/// Splits tokens on the commas that are not nested inside brackets, used for
+/// parameter and attribute lists and object initializers.
+fn split_on_commas<'a>(tokens: &[&'a Token]) -> Vec<Vec<&'a Token>> {
+    let mut parts: Vec<Vec<&Token>> = vec![vec![]];
+    let mut depth = 0;
+    let mut index = 0;
+
+    while index < tokens.len() {
+        let token = tokens[index];
+
+        // `<` and `>` are only brackets around type arguments, in
+        // `a < b, c` they compare
+        if let Some(close) = type_arguments_end(tokens, index) {
+            parts.last_mut().unwrap().extend(&tokens[index..=close]);
+            index = close + 1;
             continue;
         }
 
-        if no_spaces[0..keyword.len()] == *keyword {
-            current_keyword = keyword;
-            break;
+        if token.kind == TokenKind::Punctuation {
+            match token.text.as_str() {
+                "(" | "[" | "{" => depth += 1,
+                ")" | "]" | "}" => depth -= 1,
+                "," if depth == 0 => {
+                    parts.push(vec![]);
+                    index += 1;
+                    continue;
+                }
+                _ => (),
+            }
         }
+
+        parts.last_mut().unwrap().push(token);
+        index += 1;
     }
 
-    if current_keyword == "public" || current_keyword == "private" || current_keyword == "protected"
+    parts
+}
+
+/// The index of the `>` closing type arguments like `<string, List<int>>`
+/// opened at `open`.
+fn type_arguments_end(tokens: &[&Token], open: usize) -> Option<usize> {
+    if open == 0
+        || !is_punctuation(tokens[open], "<")
+        || tokens[open - 1].kind != TokenKind::Identifier
     {
-        let keyword_len = current_keyword.len();
+        return None;
+    }
 
-        if no_spaces[keyword_len..(keyword_len + 5)] == "class".to_string() {
-            return BlockType::Class;
-        }
+    let close = matching_bracket(tokens, open)?;
 
-        if class_name.is_some() {
-            let class_name = class_name.clone().unwrap();
-            let class_name_with_open_parens = format!("{}(", class_name);
+    tokens[open + 1..close]
+        .iter()
+        .all(|token| match token.kind {
+            TokenKind::Identifier | TokenKind::Keyword => true,
+            TokenKind::Punctuation => {
+                [",", ".", "<", ">", "?", "[", "]"].contains(&token.text.as_str())
+            }
+            _ => false,
+        })
+        .then_some(close)
+}
+
+/// Moves every brace onto a line of its own, so `if (x) {`, `} else {` and
+/// `{ return x; }` are read the same way as braces written on separate lines.
+/// Text after a `}` that continues the statement, like `});` or
+/// `}).SingleOrDefault();`, stays on the line of the brace. The source line
+/// every new line came from is returned next to the lines.
+fn split_brace_lines(data: &Data) -> (Vec<String>, Vec<u32>) {
+    let mut lines = vec![];
+    let mut line_numbers = vec![];
+
+    for (index, line) in data.lines.iter().enumerate() {
+        let tokens = line_tokens(data, index);
+        let mut cuts = vec![];
+
+        for (token_index, token) in tokens.iter().enumerate() {
+            let start = token.span.start_column as usize;
+            let end = token.span.end_column as usize;
+
+            if is_punctuation(token, "{") {
+                cuts.extend([start, end]);
+            } else if is_punctuation(token, "}") {
+                cuts.push(start);
+
+                let continues_statement = tokens.get(token_index + 1).is_some_and(|next| {
+                    next.kind == TokenKind::Punctuation && next.text != "{" && next.text != "}"
+                });
 
-            if no_spaces.contains(&class_name_with_open_parens) {
-                return BlockType::Constructor;
+                if !continues_statement {
+                    cuts.push(end);
+                }
             }
         }
 
-        return BlockType::Method;
-    }
+        let code = line.trim();
+        let is_single_brace = (code == "{" || code == "}") && cuts.len() == 2;
 
-    if current_keyword == "using" && no_spaces.contains("CreateContext") {
-        return BlockType::Context;
-    }
+        if cuts.is_empty() || is_single_brace {
+            lines.push(line.clone());
+            line_numbers.push(index as u32);
+            continue;
+        }
 
-    if current_keyword == "var" {
-        return BlockType::Variable;
-    }
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let mut previous = 0;
+
+        for cut in cuts.into_iter().chain([line.len()]) {
+            let segment = &line[previous..cut];
 
-    if current_keyword == "else" {
-        return BlockType::If;
+            if !segment.trim().is_empty() {
+                let segment = if cut == line.len() {
+                    segment.trim_start()
+                } else {
+                    segment.trim()
+                };
+
+                lines.push(format!("{}{}", indent, segment));
+                line_numbers.push(index as u32);
+            }
+
+            previous = cut;
+        }
     }
 
-    get_blocktype_from_string(current_keyword)
+    (lines, line_numbers)
 }
 
-fn set_block_types(mut data: Data) -> Data {
-    if let Some(blocks) = &mut data.blocks {
-        for block in blocks {
-            let block_type = determine_block_type(&block, &data.lines, &data.class_name);
-            block.block_type = block_type;
+fn get_open_block_index(blocks: &[Block]) -> Option<usize> {
+    blocks.iter().rposition(|block| block.end.is_none())
+}
+
+fn get_blocks(mut data: Data) -> Data {
+    let mut blocks = Vec::new();
+    let mut diagnostics = vec![];
+
+    for token in &data.tokens {
+        let line = token.span.start_line;
+
+        if is_punctuation(token, "{") {
+            blocks.push(Block {
+                start: line,
+                end: None,
+                block_type: BlockType::Unknown,
+                details: None,
+            });
+        } else if is_punctuation(token, "}") {
+            match get_open_block_index(&blocks) {
+                Some(index) => blocks[index].end = Some(line),
+                None => diagnostics.push(diagnostic(
+                    &data,
+                    DiagnosticCode::UnbalancedBraces,
+                    Severity::Error,
+                    Some(line),
+                    "closing brace has no matching opening brace",
+                )),
+            }
         }
     }
 
+    let last_line = data.lines.len().saturating_sub(1) as u32;
+
+    for block in blocks.iter_mut().filter(|block| block.end.is_none()) {
+        diagnostics.push(diagnostic(
+            &data,
+            DiagnosticCode::UnbalancedBraces,
+            Severity::Error,
+            Some(block.start),
+            "opening brace is never closed, the block is read up to the end of the file",
+        ));
+        block.end = Some(last_line);
+    }
+
+    data.blocks = Some(blocks);
+    data.diagnostics.extend(diagnostics);
+
     data
 }
 
-fn get_additional_select_blocks(data: &Data) -> Data {
-    let data = data.clone();
-    let data_blocks = data.blocks.unwrap();
-
-    let mut result = vec![];
-    for block in &data_blocks {
-        if block.block_type != BlockType::Context {
-            continue;
-        }
+/// Reads every class of the file with the namespace and class it is declared
+/// in. The first controller, or the first class when there is none, is the
+/// class the router is written for.
+fn get_classes(mut data: Data) -> Data {
+    let blocks = data.blocks.clone().unwrap_or_default();
+    let file_namespace = get_file_namespace(&data);
+    let mut classes: Vec<Class> = vec![];
+
+    for block in &blocks {
+        let tokens = header_tokens(&data, block);
+        let modifier_count = skip_modifiers(&tokens);
+
+        let name_index = match type_name_index(&tokens, modifier_count) {
+            Some(name_index) if tokens[name_index].kind == TokenKind::Identifier => name_index,
+            _ => continue,
+        };
+        let name = tokens[name_index].text.clone();
+        let is_record = is_keyword(tokens[modifier_count], "record");
 
-        let mut current_block_start: Option<u32> = None;
-        for i in block.start..block.end.unwrap() {
-            let line = &data.lines[i as usize];
+        let mut rest = &tokens[name_index + 1..];
 
-            if line.contains(&format!(
-                " {}.",
-                match block.clone().details.unwrap() {
-                    BlockDetails::ContextBlock { variable } => variable,
-                    _ => panic!("Expected context block details"),
-                }
-            )) {
-                current_block_start = Some(i);
-            }
+        if rest.first().is_some_and(|token| is_punctuation(token, "<")) {
+            rest = &rest[matching_bracket(rest, 0).map_or(rest.len(), |close| close + 1)..];
+        }
 
-            if current_block_start.is_some() && line.contains("{") {
-                current_block_start = None;
-            }
+        // the parameters of a positional record are its properties, the ones
+        // of a primary constructor are only in scope of the class
+        let mut members = vec![];
 
-            if current_block_start.is_some()
-                && (line.contains(".Add")
-                    || line.contains(".SaveChanges")
-                    || line.contains(".Remove"))
-            {
-                current_block_start = None;
-            }
+        if rest.first().is_some_and(|token| is_punctuation(token, "(")) {
+            let close = matching_bracket(rest, 0).unwrap_or(rest.len() - 1);
 
-            if current_block_start.is_some() && line.contains(";") {
-                result.push(Block {
-                    start: current_block_start.unwrap(),
-                    end: Some(i),
-                    block_type: BlockType::Select,
-                    details: None,
+            if is_record {
+                members.extend(get_parameters(&data, &rest[1..close]).into_iter().map(
+                    |parameter| Member {
+                        name: parameter.name,
+                        kind: MemberKind::Property,
+                    },
+                ));
+            } else {
+                members.push(Member {
+                    name: name.clone(),
+                    kind: MemberKind::Constructor,
                 });
-                current_block_start = None;
             }
+
+            rest = &rest[close + 1..];
         }
+
+        let encloses = |outer: &Block| {
+            outer.start < block.start && outer.end.is_none_or(|end| end > block.start)
+        };
+
+        let namespace = file_namespace
+            .iter()
+            .cloned()
+            .chain(
+                blocks
+                    .iter()
+                    .filter(|outer| encloses(outer))
+                    .filter_map(|outer| {
+                        let tokens = header_tokens(&data, outer);
+
+                        match tokens.split_first() {
+                            Some((first, name)) if is_keyword(first, "namespace") => {
+                                Some(type_text(name))
+                            }
+                            _ => None,
+                        }
+                    }),
+            )
+            .collect::<Vec<String>>();
+
+        let parent = classes
+            .iter()
+            .rev()
+            .find(|class| {
+                class.start < block.start && class.end.is_none_or(|end| end > block.start)
+            })
+            .map(|class| class.name.clone());
+
+        let attributes = get_attributes(&data, block);
+        let body = match block.end {
+            Some(end) if end > block.start + 1 => lines_tokens(&data, block.start + 1, end - 1),
+            _ => vec![],
+        };
+
+        classes.push(Class {
+            namespace: (!namespace.is_empty()).then(|| namespace.join(".")),
+            parent,
+            base_types: get_base_types(rest),
+            attributes: attributes
+                .iter()
+                .filter_map(|attribute| get_attribute_text(&data, attribute))
+                .collect(),
+            route_prefix: attributes
+                .iter()
+                .find(|attribute| get_attribute_name(attribute) == "Route")
+                .and_then(|attribute| get_attribute_template(attribute)),
+            members: [members, get_members(&body, &name)].concat(),
+            name,
+            start: block.start,
+            end: block.end,
+        });
     }
 
-    Data {
-        lines: data.lines,
-        blocks: Some([data_blocks, result].concat()),
-        class_name: data.class_name,
+    let controller = classes
+        .iter()
+        .find(|class| is_controller(class))
+        .or(classes.first())
+        .cloned();
+
+    match controller {
+        Some(controller) => {
+            data.class_name = Some(controller.name);
+            data.route_prefix = controller.route_prefix;
+            data.class_attributes = controller.attributes;
+        }
+        None => {
+            let missing_class = diagnostic(
+                &data,
+                DiagnosticCode::MissingClass,
+                Severity::Warning,
+                None,
+                "no class declaration found",
+            );
+
+            data.diagnostics.push(missing_class);
+        }
     }
+
+    data.classes = classes;
+    data
 }
 
-fn get_httptype_from_string(str: &str) -> HttpType {
-    match str {
-        "HttpGet" => HttpType::HttpGet,
-        "HttpPost" => HttpType::HttpPost,
-        "HttpPut" => HttpType::HttpPut,
-        "HttpDelete" => HttpType::HttpDelete,
-        _ => panic!("Unknown http method"),
+pub(crate) fn is_controller(class: &Class) -> bool {
+    class.name.ends_with("Controller")
+        || class
+            .base_types
+            .iter()
+            .any(|base_type| base_type.ends_with("Controller") || base_type == "ControllerBase")
+        || class
+            .attributes
+            .iter()
+            .any(|attribute| attribute == "ApiController")
+}
+
+/// The innermost class a line is in.
+pub(crate) fn enclosing_class(classes: &[Class], line: u32) -> Option<&Class> {
+    classes
+        .iter()
+        .filter(|class| class.start < line && class.end.is_none_or(|end| end >= line))
+        .max_by_key(|class| class.start)
+}
+
+/// The types after the `:` of a class declaration, `where` constraints are
+/// skipped. `Base(id)` passes the arguments of a primary constructor on and
+/// is read as `Base`.
+fn get_base_types(tokens: &[&Token]) -> Vec<String> {
+    if !tokens
+        .first()
+        .is_some_and(|token| is_punctuation(token, ":"))
+    {
+        return vec![];
     }
+
+    let end = tokens
+        .iter()
+        .position(|token| is_identifier(token, "where") || is_keyword(token, "where"))
+        .unwrap_or(tokens.len());
+
+    split_on_commas(&tokens[1..end])
+        .into_iter()
+        .filter(|base_type| !base_type.is_empty())
+        .map(|base_type| {
+            let end = base_type
+                .iter()
+                .position(|token| is_punctuation(token, "("))
+                .unwrap_or(base_type.len());
+
+            type_text(&base_type[..end])
+        })
+        .collect()
 }
 
-fn determine_http_method(data: &Data, block: &Block) -> Option<HttpType> {
-    let line = &data.lines[(block.start - 2) as usize];
-    let no_spaces = line.replace(" ", "");
+/// The index of the name of a `class`, `record`, `record class` or
+/// `record struct` declaration whose keyword is at `start`.
+fn type_name_index(tokens: &[&Token], start: usize) -> Option<usize> {
+    let keyword = tokens.get(start)?;
+
+    if is_keyword(keyword, "class") {
+        return Some(start + 1).filter(|index| *index < tokens.len());
+    }
 
-    if no_spaces.len() == 0 || !(no_spaces.as_bytes()[0] as char == '[') {
+    if !is_keyword(keyword, "record") {
         return None;
     }
 
-    let http_methods = vec!["HttpGet", "HttpPost", "HttpPut", "HttpDelete"];
+    let name_index = match tokens.get(start + 1) {
+        Some(token) if is_keyword(token, "class") || is_keyword(token, "struct") => start + 2,
+        _ => start + 1,
+    };
 
-    let mut current_http_method = None;
-    for http_method in http_methods {
-        if no_spaces[1..http_method.len() + 1] == http_method.to_string() {
-            current_http_method = Some(http_method);
-            break;
+    Some(name_index).filter(|index| *index < tokens.len())
+}
+
+/// `namespace Test.Controllers;`, the namespace of every class below it.
+fn get_file_namespace(data: &Data) -> Option<String> {
+    let tokens = data
+        .tokens
+        .iter()
+        .filter(|token| !matches!(token.kind, TokenKind::Comment | TokenKind::Directive))
+        .collect::<Vec<&Token>>();
+
+    let start = tokens
+        .iter()
+        .position(|token| is_keyword(token, "namespace"))?;
+    let end = tokens[start..]
+        .iter()
+        .position(|token| is_punctuation(token, ";") || is_punctuation(token, "{"))
+        .map(|offset| start + offset)?;
+
+    is_punctuation(tokens[end], ";").then(|| type_text(&tokens[start + 1..end]))
+}
+
+/// The members declared directly in a class body, each ends at its `;` or at
+/// the brace closing its body.
+fn get_members(tokens: &[&Token], class_name: &str) -> Vec<Member> {
+    let mut members = vec![];
+    let mut member_start = 0;
+    let mut index = 0;
+
+    while index < tokens.len() {
+        let token = tokens[index];
+
+        if is_punctuation(token, "(") || is_punctuation(token, "[") {
+            index = matching_bracket(tokens, index).unwrap_or(tokens.len() - 1);
+        } else if is_punctuation(token, ";") {
+            members.extend(get_member(&tokens[member_start..index], class_name, false));
+            member_start = index + 1;
+        } else if is_punctuation(token, "{") {
+            let close = matching_bracket(tokens, index).unwrap_or(tokens.len() - 1);
+            let is_accessor = tokens.get(index + 1).is_some_and(|accessor| {
+                ["get", "set", "init"]
+                    .iter()
+                    .any(|keyword| is_keyword(accessor, keyword))
+            });
+
+            members.extend(get_member(
+                &tokens[member_start..index],
+                class_name,
+                is_accessor,
+            ));
+
+            index = close;
+            member_start = close + 1;
         }
+
+        index += 1;
     }
 
-    if current_http_method.is_none() {
-        return determine_http_method(
-            data,
-            &Block {
-                start: block.start - 1,
-                end: block.end,
-                block_type: BlockType::Method,
-                details: Some(BlockDetails::MethodBlock {
-                    name: "".to_owned(),
-                    http_method: None,
-                    variables: vec![],
-                    uses_context: false,
-                }),
-            },
-        );
+    members
+}
+
+fn get_member(member: &[&Token], class_name: &str, is_property: bool) -> Option<Member> {
+    let mut start = 0;
+
+    while member
+        .get(start)
+        .is_some_and(|token| is_punctuation(token, "["))
+    {
+        start = matching_bracket(member, start)? + 1;
+    }
+
+    let declaration = &member[start..];
+    let declaration = &declaration[skip_modifiers(declaration)..];
+
+    if declaration.is_empty()
+        || declaration.iter().any(|token| {
+            ["class", "struct", "record", "interface", "enum"]
+                .iter()
+                .any(|keyword| is_keyword(token, keyword))
+        })
+    {
+        return None;
+    }
+
+    let position = |text: &str| {
+        declaration
+            .iter()
+            .position(|token| is_punctuation(token, text))
+            .unwrap_or(declaration.len())
+    };
+    let value_start = position("=").min(position("=>"));
+    let parameters = position("(");
+
+    if parameters < value_start && !is_property {
+        // `Get<T>(...)` is named by the token in front of its type arguments
+        let name_end = match declaration.get(parameters.checked_sub(1)?) {
+            Some(close) if is_punctuation(close, ">") => declaration[..parameters - 1]
+                .iter()
+                .rposition(|token| is_punctuation(token, "<"))?,
+            _ => parameters,
+        };
+        let name = declaration.get(name_end.checked_sub(1)?)?;
+
+        let kind = if name_end == 1 && name.text == class_name {
+            MemberKind::Constructor
+        } else {
+            MemberKind::Method
+        };
+
+        return Some(Member {
+            name: name.text.clone(),
+            kind,
+        });
     }
 
-    Some(get_httptype_from_string(current_http_method.unwrap()))
+    let name = declaration[..value_start]
+        .iter()
+        .rev()
+        .find(|token| token.kind == TokenKind::Identifier)?;
+
+    let kind = if is_property || position("=>") < position("=") {
+        MemberKind::Property
+    } else {
+        MemberKind::Field
+    };
+
+    Some(Member {
+        name: name.text.clone(),
+        kind,
+    })
 }
 
-fn attach_block_details(data: &Data) -> Data {
-    let new_data = data.clone();
-    let mut data_blocks = new_data.blocks.unwrap();
+/// Reads every class, record and struct other than a controller that has
+/// public auto-properties or positional record parameters, nested ones
+/// included.
+fn get_models(mut data: Data) -> Data {
+    let tokens = data
+        .tokens
+        .iter()
+        .filter(|token| !matches!(token.kind, TokenKind::Comment | TokenKind::Directive))
+        .collect::<Vec<&Token>>();
 
-    for index in 0..data_blocks.len() {
-        let mut block = data_blocks[index].clone();
-        if block.block_type == BlockType::Context {
-            let prev_line = &new_data.lines[block.start as usize - 1];
-            let variable = prev_line.trim_start()["using (var ".len()..]
-                .split("=")
-                .collect::<Vec<&str>>()[0]
-                .trim()
-                .to_string();
+    let mut models = vec![];
 
-            block.details = Some(BlockDetails::ContextBlock { variable });
-            data_blocks[index] = block;
-        } else if block.block_type == BlockType::Method {
-            let prev_line = &new_data.lines[block.start as usize - 1]
-                .split(" ")
-                .collect::<Vec<&str>>();
-            let name_index = prev_line.iter().position(|word| word.contains("("));
+    for (index, token) in tokens.iter().enumerate() {
+        if !["class", "record", "struct"]
+            .iter()
+            .any(|keyword| is_keyword(token, keyword))
+        {
+            continue;
+        }
 
-            if name_index.is_none() {
-                continue;
+        let name = match tokens.get(index + 1) {
+            Some(name) if name.kind == TokenKind::Identifier => name.text.clone(),
+            _ => continue,
+        };
+
+        if name.ends_with("Controller") {
+            continue;
+        }
+
+        let is_record = is_keyword(token, "record")
+            || index
+                .checked_sub(1)
+                .is_some_and(|previous| is_keyword(tokens[previous], "record"));
+
+        let mut declaration_end = index + 2;
+
+        if tokens
+            .get(declaration_end)
+            .is_some_and(|token| is_punctuation(token, "<"))
+        {
+            declaration_end = matching_bracket(&tokens, declaration_end)
+                .map_or(declaration_end, |close| close + 1);
+        }
+
+        // `record UserDto(Guid Id, string Name);` declares its properties in
+        // its parameters
+        let mut properties = vec![];
+
+        if tokens
+            .get(declaration_end)
+            .is_some_and(|token| is_punctuation(token, "("))
+        {
+            let close = match matching_bracket(&tokens, declaration_end) {
+                Some(close) => close,
+                None => continue,
+            };
+
+            if is_record {
+                properties.extend(
+                    get_parameters(&data, &tokens[declaration_end + 1..close])
+                        .into_iter()
+                        .map(|parameter| Property {
+                            name: parameter.name,
+                            property_type: parameter.variable_type,
+                        }),
+                );
             }
 
-            let name_index = name_index.unwrap();
+            declaration_end = close + 1;
+        }
+
+        let open = tokens[declaration_end.min(tokens.len())..]
+            .iter()
+            .position(|token| is_punctuation(token, "{") || is_punctuation(token, ";"))
+            .map(|offset| declaration_end + offset)
+            .filter(|open| is_punctuation(tokens[*open], "{"));
+
+        if let Some((open, close)) =
+            open.and_then(|open| Some((open, matching_bracket(&tokens, open)?)))
+        {
+            properties.extend(get_properties(&tokens[open + 1..close]));
+        }
+
+        if !properties.is_empty() {
+            models.push(Model { name, properties });
+        }
+    }
+
+    data.models = models;
+    data
+}
+
+/// The auto-properties declared directly in a class body, nested types and
+/// method bodies are skipped.
+fn get_properties(tokens: &[&Token]) -> Vec<Property> {
+    let mut properties = vec![];
+    let mut member_start = 0;
+    let mut index = 0;
+
+    while index < tokens.len() {
+        let token = tokens[index];
+
+        if is_punctuation(token, ";") || is_punctuation(token, "}") {
+            member_start = index + 1;
+        } else if is_punctuation(token, "{") {
+            let close = matching_bracket(tokens, index).unwrap_or(tokens.len() - 1);
+            let is_accessor = tokens.get(index + 1).is_some_and(|accessor| {
+                ["get", "set", "init"]
+                    .iter()
+                    .any(|keyword| is_keyword(accessor, keyword))
+            });
+
+            if is_accessor {
+                properties.extend(get_property(&tokens[member_start..index]));
+            }
+
+            index = close;
+            member_start = close + 1;
+        }
 
-            let end = prev_line[name_index].find("(").unwrap();
-            let function_name = prev_line[name_index][0..end].to_string();
+        index += 1;
+    }
+
+    properties
+}
+
+fn get_property(member: &[&Token]) -> Option<Property> {
+    let mut start = 0;
 
-            let mut variables = vec![];
+    while member
+        .get(start)
+        .is_some_and(|token| is_punctuation(token, "["))
+    {
+        start = matching_bracket(member, start)? + 1;
+    }
+
+    let modifiers = &member[start..];
+    let declaration = &modifiers[skip_modifiers(modifiers)..];
+    let declaration = match declaration.first() {
+        Some(token) if is_keyword(token, "required") || is_identifier(token, "required") => {
+            &declaration[1..]
+        }
+        _ => declaration,
+    };
+
+    let is_public = modifiers.iter().any(|token| is_keyword(token, "public"));
+    let is_plain = !declaration.iter().any(|token| {
+        is_punctuation(token, "(")
+            || is_punctuation(token, "=")
+            || MEMBER_KEYWORDS.contains(&token.text.as_str())
+    });
 
-            let mut previous_type = prev_line[name_index][end + 1..].to_string();
-            let mut next_index = name_index + 1;
+    match declaration.split_last() {
+        Some((name, property_type))
+            if is_public
+                && is_plain
+                && name.kind == TokenKind::Identifier
+                && !property_type.is_empty() =>
+        {
+            Some(Property {
+                name: name.text.clone(),
+                property_type: type_text(property_type),
+            })
+        }
+        _ => None,
+    }
+}
 
-            while next_index < prev_line.len() {
-                let has_comma = prev_line[next_index].contains(",");
-                let has_end = prev_line[next_index].contains(")");
+/// The text of a type, `Dictionary<string, int>` keeps its space after the
+/// comma and everything else is written without spaces.
+fn type_text(tokens: &[&Token]) -> String {
+    tokens
+        .iter()
+        .map(|token| {
+            if is_punctuation(token, ",") {
+                ", ".to_string()
+            } else {
+                token.text.clone()
+            }
+        })
+        .collect()
+}
+
+/// The attributes written on the lines above a declaration or in front of it
+/// on the same line, each entry holds the tokens of one attribute such as
+/// `HttpGet("items/{id}")`.
+fn get_attributes<'a>(data: &'a Data, block: &Block) -> Vec<Vec<&'a Token>> {
+    if block.start == 0 {
+        return vec![];
+    }
+
+    let declaration_line = declaration_start(data, block) as usize;
+    let mut lines = vec![declaration_line];
+    let mut index = declaration_line;
+
+    while index > 0 {
+        index -= 1;
+
+        match line_tokens(data, index).first() {
+            Some(token) if is_punctuation(token, "[") => lines.insert(0, index),
+            _ => break,
+        }
+    }
+
+    let mut attributes = vec![];
+
+    for line in lines {
+        let tokens = line_tokens(data, line);
+        let mut start = 0;
+
+        while tokens
+            .get(start)
+            .is_some_and(|token| is_punctuation(token, "["))
+        {
+            let end = match matching_bracket(&tokens, start) {
+                Some(end) => end,
+                None => break,
+            };
+
+            attributes.extend(
+                split_on_commas(&tokens[start + 1..end])
+                    .into_iter()
+                    .filter(|attribute| !attribute.is_empty()),
+            );
+
+            start = end + 1;
+        }
+    }
+
+    attributes
+}
+
+fn get_attribute_name(attribute: &[&Token]) -> String {
+    attribute
+        .first()
+        .map(|token| token.text.clone())
+        .unwrap_or_default()
+}
+
+fn get_attribute_text(data: &Data, attribute: &[&Token]) -> Option<String> {
+    Some(source_text(data, attribute.first()?, attribute.last()?))
+}
+
+fn get_attribute_template(attribute: &[&Token]) -> Option<String> {
+    attribute
+        .iter()
+        .find(|token| is_string(token))
+        .map(|token| {
+            token
+                .text
+                .trim_start_matches(['$', '@'])
+                .trim_matches('"')
+                .to_string()
+        })
+}
+
+fn determine_block_type(block: &Block, data: &Data) -> BlockType {
+    let tokens = header_tokens(data, block);
+
+    let first = match tokens.first() {
+        Some(first) => first,
+        None => return BlockType::Unknown,
+    };
+
+    match first.text.as_str() {
+        "namespace" => return BlockType::Namespace,
+        "class" | "record" => return BlockType::Class,
+        "select" => return BlockType::Select,
+        "if" | "else" => return BlockType::If,
+        "var" => return BlockType::Variable,
+        "using" => {
+            if tokens
+                .iter()
+                .any(|token| is_identifier(token, "CreateContext"))
+            {
+                return BlockType::Context;
+            }
+
+            return BlockType::Unknown;
+        }
+        _ => (),
+    }
+
+    let modifier_count = skip_modifiers(&tokens);
+
+    if modifier_count == 0 {
+        return BlockType::Unknown;
+    }
+
+    if type_name_index(&tokens, modifier_count).is_some() {
+        return BlockType::Class;
+    }
+
+    if let Some(class) = enclosing_class(&data.classes, block.start) {
+        let class_name = &class.name;
+        let is_constructor = tokens
+            .windows(2)
+            .any(|pair| is_identifier(pair[0], class_name) && is_punctuation(pair[1], "("));
+
+        if is_constructor {
+            return BlockType::Constructor;
+        }
+    }
+
+    // the `{ get; set; }` of a property
+    if !tokens.iter().any(|token| is_punctuation(token, "(")) {
+        return BlockType::Unknown;
+    }
+
+    BlockType::Method
+}
+
+fn set_block_types(mut data: Data) -> Data {
+    if let Some(mut blocks) = data.blocks.take() {
+        for block in blocks.iter_mut() {
+            block.block_type = determine_block_type(block, &data);
+        }
+
+        data.blocks = Some(blocks);
+    }
+
+    data
+}
+
+fn is_write_operation(tokens: &[&Token], index: usize) -> bool {
+    index > 0
+        && is_punctuation(tokens[index - 1], ".")
+        && tokens[index].kind == TokenKind::Identifier
+        && WRITE_OPERATIONS.contains(&tokens[index].text.as_str())
+}
+
+fn get_additional_select_blocks(data: &Data) -> Data {
+    let data = data.clone();
+    let data_blocks = data.blocks.clone().unwrap();
+
+    let mut result = vec![];
+    for block in &data_blocks {
+        let variable = match &block.details {
+            Some(BlockDetails::ContextBlock { variable }) => variable,
+            _ => continue,
+        };
+
+        let mut current_block_start: Option<u32> = None;
+        let mut is_query = false;
+        let mut depth = 0;
+
+        for i in block.start..block.end.unwrap() {
+            let tokens = line_tokens(&data, i as usize);
+
+            let uses_context = (0..tokens.len().saturating_sub(1)).any(|index| {
+                is_identifier(tokens[index], variable)
+                    && is_punctuation(tokens[index + 1], ".")
+                    && (index == 0 || !is_punctuation(tokens[index - 1], "."))
+            });
+
+            if uses_context && current_block_start.is_none() {
+                current_block_start = Some(i);
+                is_query = is_query_start(&tokens);
+                depth = 0;
+            }
+
+            // a lambda chain keeps the braces of `.Select(x => new { ... })`,
+            // query syntax and statements like `if` are blocks of their own
+            for token in &tokens {
+                if is_punctuation(token, "(") {
+                    depth += 1;
+                } else if is_punctuation(token, ")") {
+                    depth -= 1;
+                } else if is_punctuation(token, "{") && (is_query || depth <= 0) {
+                    current_block_start = None;
+                }
+            }
+
+            if current_block_start.is_some()
+                && (0..tokens.len()).any(|index| is_write_operation(&tokens, index))
+            {
+                current_block_start = None;
+            }
+
+            if current_block_start.is_some()
+                && tokens.iter().any(|token| is_punctuation(token, ";"))
+            {
+                result.push(Block {
+                    start: current_block_start.unwrap(),
+                    end: Some(i),
+                    block_type: BlockType::Select,
+                    details: None,
+                });
+                current_block_start = None;
+            }
+        }
+    }
+
+    Data {
+        lines: data.lines,
+        blocks: Some([data_blocks, result].concat()),
+        class_name: data.class_name,
+        route_prefix: data.route_prefix,
+        class_attributes: data.class_attributes,
+        tokens: data.tokens,
+        line_numbers: data.line_numbers,
+        diagnostics: data.diagnostics,
+        models: data.models,
+        classes: data.classes,
+    }
+}
+
+fn get_httptype_from_string(str: &str) -> Option<HttpType> {
+    match str {
+        "HttpGet" => Some(HttpType::HttpGet),
+        "HttpPost" => Some(HttpType::HttpPost),
+        "HttpPut" => Some(HttpType::HttpPut),
+        "HttpPatch" => Some(HttpType::HttpPatch),
+        "HttpDelete" => Some(HttpType::HttpDelete),
+        _ => None,
+    }
+}
+
+fn determine_http_method(data: &Data, block: &Block) -> Option<HttpType> {
+    get_attributes(data, block)
+        .iter()
+        .rev()
+        .find_map(|attribute| get_httptype_from_string(&get_attribute_name(attribute)))
+}
+
+/// The names of `Http*` attributes the routers have no verb for, like
+/// `[HttpHead]`.
+fn get_unsupported_http_attributes<'a>(data: &'a Data, block: &Block) -> Vec<&'a Token> {
+    get_attributes(data, block)
+        .into_iter()
+        .filter_map(|attribute| attribute.first().copied())
+        .filter(|name| {
+            name.text.starts_with("Http") && get_httptype_from_string(&name.text).is_none()
+        })
+        .collect()
+}
+
+fn determine_route_template(data: &Data, block: &Block) -> Option<String> {
+    let mut route_template = None;
+
+    for attribute in get_attributes(data, block).iter().rev() {
+        let name = get_attribute_name(attribute);
+        let template = get_attribute_template(attribute);
+
+        if get_httptype_from_string(&name).is_some() && template.is_some() {
+            return template;
+        }
+
+        if name == "Route" && route_template.is_none() {
+            route_template = template;
+        }
+    }
+
+    route_template
+}
+
+fn determine_route(data: &Data, block: &Block, method_name: &str) -> Option<Route> {
+    let template = determine_route_template(data, block);
+
+    if template.is_none() && determine_http_method(data, block).is_none() {
+        return None;
+    }
+
+    // a method of a second controller has the prefix of its own class
+    match enclosing_class(&data.classes, block.start) {
+        Some(class) if is_controller(class) => Some(parse_route(
+            &class.route_prefix,
+            &template,
+            &Some(class.name.clone()),
+            method_name,
+        )),
+        _ => Some(parse_route(
+            &data.route_prefix,
+            &template,
+            &data.class_name,
+            method_name,
+        )),
+    }
+}
+
+/// Splits a parameter list on its top level commas, attributes, modifiers and
+/// default values are dropped so only the type and the name remain.
+fn get_parameters(data: &Data, tokens: &[&Token]) -> Vec<Variable> {
+    let mut variables = vec![];
+
+    for parameter in split_on_commas(tokens) {
+        let mut start = 0;
+
+        if parameter
+            .first()
+            .is_some_and(|token| is_punctuation(token, "["))
+        {
+            start = matching_bracket(&parameter, 0).map_or(parameter.len(), |end| end + 1);
+        }
+
+        let end = parameter
+            .iter()
+            .position(|token| is_punctuation(token, "="))
+            .unwrap_or(parameter.len());
+
+        while start < end && PARAMETER_MODIFIERS.contains(&parameter[start].text.as_str()) {
+            start += 1;
+        }
+
+        if end < start + 2 {
+            continue;
+        }
+
+        variables.push(Variable {
+            name: parameter[end - 1].text.clone(),
+            variable_type: source_text(data, parameter[start], parameter[end - 2]),
+            default_value: parameter
+                .get(end + 1)
+                .map(|first| source_text(data, first, parameter[parameter.len() - 1])),
+        });
+    }
+
+    variables
+}
+
+/// The names an expression can refer to at a line: the parameters of the
+/// method it is in and the locals declared above it.
+struct Scope {
+    parameters: Vec<String>,
+    locals: Vec<String>,
+}
+
+fn get_scope(data: &Data, blocks: &[Block], line: u32) -> Scope {
+    let method = blocks.iter().rev().find_map(|block| match &block.details {
+        Some(BlockDetails::MethodBlock { variables, .. })
+            if block.start < line && block.end.is_some_and(|end| end > line) =>
+        {
+            Some((block.start, variables))
+        }
+        _ => None,
+    });
+
+    let (start, parameters) = match method {
+        Some((start, variables)) => (
+            start,
+            variables
+                .iter()
+                .map(|variable| variable.name.clone())
+                .collect(),
+        ),
+        None => (line, vec![]),
+    };
+
+    let locals = (start + 1..line)
+        .filter_map(|line| {
+            let tokens = line_tokens(data, line as usize);
+            let equals = tokens.iter().position(|token| is_punctuation(token, "="))?;
+
+            // a type has to come before the name for it to be a declaration
+            if equals < 2 || tokens[equals - 2].kind == TokenKind::Punctuation {
+                return None;
+            }
+
+            get_assigned_name(&tokens)
+        })
+        .collect();
+
+    Scope { parameters, locals }
+}
+
+/// The parameters, locals and static members an expression reads, in the
+/// order they are first read.
+fn get_references(tokens: &[&Token], scope: &Scope) -> Vec<Reference> {
+    let mut references = vec![];
+    let mut index = 0;
+
+    while index < tokens.len() {
+        let starts_chain = tokens[index].kind == TokenKind::Identifier
+            && (index == 0
+                || !(is_punctuation(tokens[index - 1], ".")
+                    || is_keyword(tokens[index - 1], "new")));
+
+        if !starts_chain {
+            index += 1;
+            continue;
+        }
+
+        let name = tokens[index].text.clone();
+        let mut path = vec![];
+
+        while index + 2 < tokens.len()
+            && is_punctuation(tokens[index + 1], ".")
+            && tokens[index + 2].kind == TokenKind::Identifier
+        {
+            path.push(tokens[index + 2].text.clone());
+            index += 2;
+        }
+
+        let is_call = tokens
+            .get(index + 1)
+            .is_some_and(|token| is_punctuation(token, "("));
+
+        let reference = if scope.parameters.contains(&name) {
+            Reference::Parameter { name, path }
+        } else if scope.locals.contains(&name) {
+            Reference::Variable { name, path }
+        } else {
+            let name = [vec![name], path].concat().join(".");
+
+            if is_call {
+                Reference::Call { name }
+            } else {
+                Reference::Member { name }
+            }
+        };
+
+        if !references.contains(&reference) {
+            references.push(reference);
+        }
+
+        index += 1;
+    }
+
+    references
+}
+
+/// Reads `Property = expression`.
+fn get_assignment(data: &Data, tokens: &[&Token], scope: &Scope) -> Option<Assignment> {
+    match tokens {
+        [property, equals, value @ ..]
+            if property.kind == TokenKind::Identifier
+                && is_punctuation(equals, "=")
+                && !value.is_empty() =>
+        {
+            Some(Assignment {
+                property: property.text.clone(),
+                value: source_text(data, value[0], value[value.len() - 1]),
+                references: get_references(value, scope),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// The `Property = expression` entries of an object initializer in source
+/// order, collection initializers have none.
+fn get_initializer_assignments(data: &Data, block: &Block, scope: &Scope) -> Vec<Assignment> {
+    let tokens = (block.start + 1..block.end.unwrap_or(block.start))
+        .flat_map(|line| line_tokens(data, line as usize))
+        .collect::<Vec<&Token>>();
+
+    split_on_commas(&tokens)
+        .iter()
+        .filter_map(|part| get_assignment(data, part, scope))
+        .collect()
+}
+
+fn attach_block_details(data: &Data) -> Data {
+    let mut new_data = data.clone();
+    let mut data_blocks = new_data.blocks.clone().unwrap();
+    let mut diagnostics = vec![];
+
+    for index in 0..data_blocks.len() {
+        let mut block = data_blocks[index].clone();
+        let tokens = header_tokens(&new_data, &block);
+
+        if block.block_type == BlockType::Context {
+            let variable = tokens
+                .iter()
+                .position(|token| is_keyword(token, "var"))
+                .and_then(|index| tokens.get(index + 1))
+                .map(|token| token.text.clone())
+                .unwrap_or_default();
+
+            block.details = Some(BlockDetails::ContextBlock { variable });
+            data_blocks[index] = block;
+        } else if block.block_type == BlockType::Method {
+            let name_index = tokens.windows(2).position(|pair| {
+                pair[0].kind == TokenKind::Identifier && is_punctuation(pair[1], "(")
+            });
+
+            if name_index.is_none() {
+                diagnostics.push(diagnostic(
+                    data,
+                    DiagnosticCode::UnreadableMethod,
+                    Severity::Warning,
+                    Some(block.start.saturating_sub(1)),
+                    "could not find the name of the method declaration",
+                ));
+                continue;
+            }
+
+            for attribute in get_unsupported_http_attributes(data, &block) {
+                diagnostics.push(diagnostic(
+                    data,
+                    DiagnosticCode::UnsupportedHttpMethod,
+                    Severity::Warning,
+                    Some(attribute.span.start_line),
+                    &format!("[{}] is not supported and is ignored", attribute.text),
+                ));
+            }
+
+            let name_index = name_index.unwrap();
+            let function_name = tokens[name_index].text.clone();
+
+            let parameters_end = matching_bracket(&tokens, name_index + 1).unwrap_or(tokens.len());
+            let variables = get_parameters(&new_data, &tokens[name_index + 2..parameters_end]);
+
+            let route = determine_route(data, &block, &function_name);
+
+            block.details = Some(BlockDetails::MethodBlock {
+                name: function_name,
+                variables,
+                http_method: determine_http_method(data, &block),
+                route,
+                uses_context: data_blocks.iter().any(|b| {
+                    b.start > block.start
+                        && b.end.unwrap() < block.end.unwrap()
+                        && b.block_type == BlockType::Context
+                }),
+            });
+
+            data_blocks[index] = block;
+        } else if block.block_type == BlockType::Variable {
+            let name = tokens
+                .get(1)
+                .map(|token| token.text.clone())
+                .unwrap_or_default();
+
+            let new_index = tokens.iter().position(|token| is_keyword(token, "new"));
+            let type_end = tokens
+                .iter()
+                .skip(new_index.unwrap_or(0))
+                .position(|token| is_punctuation(token, "(") || is_punctuation(token, "{"))
+                .map_or(tokens.len(), |end| end + new_index.unwrap_or(0));
+
+            let data_type = match new_index {
+                Some(new_index) if new_index + 1 < type_end => {
+                    source_text(&new_data, tokens[new_index + 1], tokens[type_end - 1])
+                }
+                _ => tokens[0].text.clone(),
+            };
+
+            let scope = get_scope(&new_data, &data_blocks, block.start);
+            let assignments = get_initializer_assignments(&new_data, &block, &scope);
+
+            block.details = Some(BlockDetails::VariableBlock {
+                name,
+                data_type,
+                assignments,
+            });
+            data_blocks[index] = block;
+        } else if block.block_type == BlockType::If {
+            let has_else = tokens
+                .first()
+                .is_some_and(|token| is_keyword(token, "else"));
+
+            let clause = tokens
+                .iter()
+                .position(|token| is_keyword(token, "if"))
+                .and_then(|if_index| {
+                    let open = if_index + 1;
+                    let close = matching_bracket(&tokens, open)?;
+
+                    if close < open + 2 {
+                        return None;
+                    }
+
+                    Some(source_text(&new_data, tokens[open + 1], tokens[close - 1]))
+                })
+                .unwrap_or_default();
+
+            block.details = Some(BlockDetails::IfBlock {
+                clause,
+                is_else: has_else,
+            });
+            data_blocks[index] = block;
+        }
+    }
+
+    new_data.diagnostics.extend(diagnostics);
+
+    Data {
+        lines: new_data.lines,
+        blocks: Some(data_blocks),
+        class_name: new_data.class_name,
+        route_prefix: new_data.route_prefix,
+        class_attributes: new_data.class_attributes,
+        tokens: new_data.tokens,
+        line_numbers: new_data.line_numbers,
+        diagnostics: new_data.diagnostics,
+        models: new_data.models,
+        classes: new_data.classes,
+    }
+}
+
+fn get_frequency_and_return_data(
+    tables: &[Table],
+    return_data: &[ReturnData],
+) -> (Vec<Table>, Vec<ReturnData>) {
+    let mut tables = tables.to_vec();
+    let mut map: HashMap<String, i32> = HashMap::new();
+
+    for data in return_data.iter().cloned() {
+        let old_value = map.get(&data.table).unwrap_or(&0);
+        map.insert(data.table, *old_value + 1);
+    }
+
+    tables.iter_mut().for_each(|table| {
+        table.return_frequency = map.get(&table.name).unwrap_or(&0).to_owned();
+    });
+
+    let mut return_data = return_data.to_vec();
+    return_data.sort_by(|a, b| {
+        map.get(&b.table)
+            .unwrap_or(&0)
+            .cmp(map.get(&a.table).unwrap_or(&0))
+    });
+
+    (tables, return_data)
+}
+
+/// The variable of the `using (var cx = ...CreateContext())` block the
+/// select block is written in.
+fn get_context_variable(blocks: &[Block], block: &Block) -> Option<String> {
+    blocks
+        .iter()
+        .rev()
+        .find(|context| {
+            context.block_type == BlockType::Context
+                && context.start < block.start
+                && context.end >= block.end
+        })
+        .and_then(|context| match &context.details {
+            Some(BlockDetails::ContextBlock { variable }) => Some(variable.clone()),
+            _ => None,
+        })
+}
+
+fn is_query_line(tokens: &[&Token]) -> bool {
+    tokens.iter().any(|token| {
+        (token.kind == TokenKind::Keyword && QUERY_KEYWORDS.contains(&token.text.as_str()))
+            || is_punctuation(token, "&&")
+    })
+}
+
+/// A query starts with `from`, a line with a lambda chain can still have a
+/// `&&` in it.
+fn is_query_start(tokens: &[&Token]) -> bool {
+    tokens.iter().any(|token| is_keyword(token, "from"))
+}
+
+/// Reads `from x in cx.Table` and `join x in cx.Table on ...` clauses.
+fn get_query_table(tokens: &[&Token], keyword_index: usize) -> Option<Table> {
+    let shortcut = tokens.get(keyword_index + 1)?.text.clone();
+    let in_index = keyword_index
+        + tokens[keyword_index..]
+            .iter()
+            .position(|token| is_keyword(token, "in"))?;
+
+    let mut name = tokens.get(in_index + 1)?.text.clone();
+    let mut index = in_index + 2;
+
+    while index + 1 < tokens.len()
+        && is_punctuation(tokens[index], ".")
+        && tokens[index + 1].kind == TokenKind::Identifier
+    {
+        name = tokens[index + 1].text.clone();
+        index += 2;
+    }
+
+    Some(Table {
+        name,
+        shortcut,
+        joined_tables: vec![],
+        return_frequency: 0,
+        join: if is_keyword(tokens[keyword_index], "join") {
+            get_join(tokens, keyword_index)
+        } else {
+            None
+        },
+    })
+}
+
+/// Reads `on ut.UserOid equals u.UserOid into userTasks` after a `join`,
+/// composite keys like `on new { a.X, a.Y } equals new { b.X, b.Y }` are
+/// paired up in order.
+fn get_join(tokens: &[&Token], keyword_index: usize) -> Option<Join> {
+    let position = |keyword: &str, start: usize| {
+        tokens[start..]
+            .iter()
+            .position(|token| is_keyword(token, keyword))
+            .map(|index| index + start)
+    };
+
+    let on = position("on", keyword_index)?;
+    let equals = position("equals", on)?;
+    let into = position("into", equals);
+
+    let (shortcuts, properties) = member_accesses(&tokens[on + 1..equals]);
+    let (_, joined_properties) = member_accesses(&tokens[equals + 1..into.unwrap_or(tokens.len())]);
+
+    let keys = shortcuts
+        .into_iter()
+        .zip(properties)
+        .zip(joined_properties)
+        .map(|((shortcut, property), joined_property)| JoinKey {
+            shortcut,
+            property,
+            joined_property,
+        })
+        .collect();
+
+    let group = into
+        .and_then(|into| tokens.get(into + 1))
+        .map(|token| token.text.clone());
+
+    Some(Join {
+        kind: if group.is_some() {
+            JoinKind::Group
+        } else {
+            JoinKind::Inner
+        },
+        keys,
+        group,
+    })
+}
+
+/// Reads `from x in group.DefaultIfEmpty()` into the group and the shortcut
+/// its rows are read with from then on.
+fn get_left_join(tokens: &[&Token], keyword_index: usize) -> Option<(String, String)> {
+    match tokens.get(keyword_index + 1..keyword_index + 6)? {
+        [shortcut, in_keyword, group, dot, method]
+            if is_keyword(in_keyword, "in")
+                && is_punctuation(dot, ".")
+                && is_identifier(method, "DefaultIfEmpty") =>
+        {
+            Some((group.text.clone(), shortcut.text.clone()))
+        }
+        _ => None,
+    }
+}
+
+/// The tables joined to a table, each with the tables joined to it in turn.
+fn get_joined_tables(tables: &[Table], shortcut: &str) -> Vec<Table> {
+    tables
+        .iter()
+        .filter(|table| {
+            table.shortcut != shortcut
+                && table
+                    .join
+                    .as_ref()
+                    .and_then(|join| join.keys.first())
+                    .is_some_and(|key| key.shortcut == shortcut)
+        })
+        .map(|table| Table {
+            joined_tables: get_joined_tables(tables, &table.shortcut),
+            ..table.clone()
+        })
+        .collect()
+}
+
+const COMPARISON_OPERATORS: &[&str] = &["==", "!=", "<=", ">=", "<", ">"];
+
+/// The position of the last `operator` outside of brackets.
+fn last_top_level(tokens: &[&Token], operator: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut position = None;
+
+    for (index, token) in tokens.iter().enumerate() {
+        if token.kind != TokenKind::Punctuation {
+            continue;
+        }
+
+        match token.text.as_str() {
+            "(" | "[" | "{" => depth += 1,
+            ")" | "]" | "}" => depth -= 1,
+            text if depth == 0 && text == operator => position = Some(index),
+            _ => (),
+        }
+    }
+
+    position
+}
+
+/// Reads a condition into an expression tree. `||` binds loosest, then `&&`,
+/// then the comparisons, `shortcuts` are the variables the query reads its
+/// tables with.
+fn get_expression(
+    data: &Data,
+    tokens: &[&Token],
+    shortcuts: &[String],
+    scope: &Scope,
+) -> Expression {
+    let mut tokens = tokens;
+
+    while tokens.len() > 2
+        && is_punctuation(tokens[0], "(")
+        && matching_bracket(tokens, 0) == Some(tokens.len() - 1)
+    {
+        tokens = &tokens[1..tokens.len() - 1];
+    }
+
+    if tokens.is_empty() {
+        return Expression::Raw {
+            text: String::new(),
+        };
+    }
+
+    let binary = |index: usize| Expression::Binary {
+        operator: tokens[index].text.clone(),
+        left: Box::new(get_expression(data, &tokens[..index], shortcuts, scope)),
+        right: Box::new(get_expression(data, &tokens[index + 1..], shortcuts, scope)),
+    };
+
+    for operator in ["||", "&&"] {
+        if let Some(index) = last_top_level(tokens, operator).filter(|index| *index > 0) {
+            return binary(index);
+        }
+    }
+
+    let comparison = COMPARISON_OPERATORS
+        .iter()
+        .filter_map(|operator| last_top_level(tokens, operator))
+        .filter(|index| *index > 0)
+        .min();
+
+    if let Some(index) = comparison {
+        return binary(index);
+    }
+
+    if is_punctuation(tokens[0], "!") && tokens.len() > 1 {
+        return Expression::Not {
+            operand: Box::new(get_expression(data, &tokens[1..], shortcuts, scope)),
+        };
+    }
+
+    get_operand(data, tokens, shortcuts, scope)
+}
+
+/// A literal, a member access chain like `ut.User.Name` or a method called
+/// on one, like `ut.Name.Contains(search)`.
+fn get_operand(data: &Data, tokens: &[&Token], shortcuts: &[String], scope: &Scope) -> Expression {
+    let raw = || Expression::Raw {
+        text: source_text(data, tokens[0], tokens[tokens.len() - 1]),
+    };
+
+    if let [token] = tokens {
+        let is_literal = matches!(
+            token.kind,
+            TokenKind::String
+                | TokenKind::VerbatimString
+                | TokenKind::InterpolatedString
+                | TokenKind::Char
+                | TokenKind::Number
+        ) || ["null", "true", "false"]
+            .iter()
+            .any(|keyword| is_keyword(token, keyword));
+
+        if is_literal {
+            return Expression::Literal {
+                value: token.text.clone(),
+            };
+        }
+    }
+
+    if tokens[0].kind != TokenKind::Identifier {
+        return raw();
+    }
+
+    let mut names = vec![tokens[0].text.clone()];
+    let mut end = 1;
+
+    while end + 1 < tokens.len()
+        && is_punctuation(tokens[end], ".")
+        && tokens[end + 1].kind == TokenKind::Identifier
+    {
+        names.push(tokens[end + 1].text.clone());
+        end += 2;
+    }
+
+    if end == tokens.len() {
+        return get_chain(names, shortcuts, scope);
+    }
+
+    let is_call = names.len() > 1
+        && is_punctuation(tokens[end], "(")
+        && matching_bracket(tokens, end) == Some(tokens.len() - 1);
+
+    if !is_call {
+        return raw();
+    }
+
+    let arguments = match tokens.len() - end {
+        2 => vec![],
+        _ => split_on_commas(&tokens[end + 1..tokens.len() - 1])
+            .iter()
+            .map(|argument| get_expression(data, argument, shortcuts, scope))
+            .collect(),
+    };
+    let method = names.pop().unwrap();
+
+    Expression::Call {
+        target: Box::new(get_chain(names, shortcuts, scope)),
+        method,
+        arguments,
+    }
+}
+
+fn get_chain(names: Vec<String>, shortcuts: &[String], scope: &Scope) -> Expression {
+    let name = names[0].clone();
+    let path = names[1..].to_vec();
+
+    if shortcuts.contains(&name) {
+        Expression::Column {
+            shortcut: name,
+            path,
+        }
+    } else if scope.parameters.contains(&name) {
+        Expression::Parameter { name, path }
+    } else if scope.locals.contains(&name) {
+        Expression::Variable { name, path }
+    } else {
+        Expression::Raw {
+            text: names.join("."),
+        }
+    }
+}
+
+fn attach_select_block_details(data: &Data) -> Data {
+    let mut final_data = data.clone();
+    let new_data = data.clone();
+    let mut data_blocks = new_data.blocks.unwrap();
+    let all_blocks = data_blocks.clone();
+
+    for data_block in data_blocks.iter_mut() {
+        let block = data_block.clone();
+        if block.block_type != BlockType::Select {
+            continue;
+        }
+
+        let mut block_syntax = LinqSyntax::Query;
+        let mut where_clauses = vec![];
+        let mut tables = vec![];
+        let mut has_return = false;
+        let mut projection = None;
+        let mut chain = vec![];
+        let mut order_by = vec![];
+        let scope = get_scope(data, &all_blocks, block.start);
+        let braces = data.lines[block.start as usize]
+            .trim_start()
+            .starts_with('{');
+
+        for index in block.start..=block.end.unwrap() {
+            let tokens = line_tokens(data, index as usize);
+
+            if index == block.start {
+                // query syntax blocks are the braces of `select new { ... }`
+                // after the query, or the statement of a query without them
+                let query_lines = if braces {
+                    let mut lines = vec![];
+                    let mut current_index = index as usize;
+
+                    while current_index > 0 && is_query_line(&line_tokens(data, current_index - 1))
+                    {
+                        current_index -= 1;
+                        lines.push(current_index);
+                    }
+
+                    lines
+                } else if is_query_start(&tokens) {
+                    (block.start as usize..=block.end.unwrap() as usize).collect()
+                } else {
+                    block_syntax = LinqSyntax::Lambda;
+                    vec![]
+                };
+
+                if block_syntax == LinqSyntax::Query {
+                    let mut conditions = vec![];
+                    let mut unread_filters = vec![];
+                    let mut orderings = vec![];
+                    let mut left_joins = vec![];
+
+                    for current_index in query_lines {
+                        let tokens = line_tokens(data, current_index);
+
+                        for (token_index, token) in tokens.iter().enumerate() {
+                            if !is_keyword(token, "from") && !is_keyword(token, "join") {
+                                continue;
+                            }
+
+                            if let Some(left_join) = get_left_join(&tokens, token_index) {
+                                left_joins.push(left_join);
+                            } else if let Some(table) = get_query_table(&tokens, token_index) {
+                                tables.push(table);
+                            }
+                        }
+
+                        let condition_index = tokens.iter().position(|token| {
+                            is_keyword(token, "where") || is_punctuation(token, "&&")
+                        });
+
+                        if let Some(condition_index) = condition_index {
+                            let end = get_clause_end(&tokens, condition_index + 1);
+                            let (shortcut, property) =
+                                member_accesses(&tokens[condition_index..end]);
+
+                            if !shortcut.is_empty() {
+                                where_clauses.push(WhereClause {
+                                    shortcut,
+                                    property,
+                                    value: source_text(
+                                        data,
+                                        tokens[condition_index],
+                                        tokens[end - 1],
+                                    ),
+                                    lambda_varible: None,
+                                    expression: None,
+                                });
+                                conditions.push((current_index, condition_index + 1, end));
+                            } else {
+                                unread_filters.push(unread_filter(
+                                    source_text(data, tokens[condition_index], tokens[end - 1]),
+                                    None,
+                                ));
+                            }
+                        }
+
+                        if let Some(orderby) =
+                            tokens.iter().position(|token| is_keyword(token, "orderby"))
+                        {
+                            orderings.push((
+                                current_index,
+                                orderby + 1,
+                                get_clause_end(&tokens, orderby + 1),
+                            ));
+                        }
+
+                        if tokens.iter().any(|token| is_keyword(token, "return")) {
+                            has_return = true;
+                        }
+                    }
+
+                    for (group, shortcut) in left_joins {
+                        let table = tables.iter_mut().find(|table| {
+                            table
+                                .join
+                                .as_ref()
+                                .is_some_and(|join| join.group.as_ref() == Some(&group))
+                        });
+
+                        if let Some(table) = table {
+                            table.shortcut = shortcut;
+                            table.join.as_mut().unwrap().kind = JoinKind::Left;
+                        }
+                    }
+
+                    // the tables are only known once every query line is read
+                    let shortcuts = tables
+                        .iter()
+                        .map(|table| table.shortcut.clone())
+                        .collect::<Vec<String>>();
+
+                    for (clause, (line, start, end)) in where_clauses.iter_mut().zip(conditions) {
+                        let tokens = line_tokens(data, line);
+                        clause.expression = Some(get_expression(
+                            data,
+                            &tokens[start..end],
+                            &shortcuts,
+                            &scope,
+                        ));
+                    }
+
+                    where_clauses.extend(unread_filters);
+                    projection = get_query_projection(data, &block, &shortcuts);
+
+                    // the lines before braces are read bottom up
+                    orderings.sort_by_key(|(line, _, _)| *line);
+
+                    for (line, start, end) in orderings {
+                        let tokens = line_tokens(data, line);
+                        order_by.extend(get_query_order_keys(
+                            data,
+                            &tokens[start..end],
+                            &shortcuts,
+                            &scope,
+                        ));
+                    }
+                }
+
+                if block_syntax == LinqSyntax::Lambda {
+                    let chain_tokens = lines_tokens(data, block.start, block.end.unwrap());
+
+                    if let Some(start) = get_chain_start(&all_blocks, &block, &chain_tokens) {
+                        for (operator, body) in get_operators(data, &chain_tokens, start) {
+                            if let Some(clause) =
+                                get_operator_filter(data, &operator, &body, &scope)
+                            {
+                                where_clauses.push(clause);
+                            }
+
+                            chain.push((operator, body));
+                        }
+                    }
+
+                    if let Some(variable) = get_context_variable(&all_blocks, &block) {
+                        for window in tokens.windows(3) {
+                            if is_identifier(window[0], &variable)
+                                && is_punctuation(window[1], ".")
+                                && window[2].kind == TokenKind::Identifier
+                            {
+                                tables.push(Table {
+                                    name: window[2].text.clone(),
+                                    shortcut: variable.clone(),
+                                    joined_tables: vec![],
+                                    return_frequency: 0,
+                                    join: None,
+                                });
+                            }
+                        }
+                    }
+
+                    if tokens.iter().any(|token| is_keyword(token, "return")) {
+                        has_return = true;
+                    }
+                }
+
+                if block.start != block.end.unwrap() {
+                    continue;
+                }
+            }
+
+            if index == block.end.unwrap() {
+                let final_tokens =
+                    if !braces || tokens.iter().any(|token| is_punctuation(token, ")")) {
+                        tokens
+                    } else {
+                        line_tokens(data, index as usize + 1)
+                    };
+
+                // `(from ... select ...).OrderBy(x => x.Name).ToList()`
+                if block_syntax == LinqSyntax::Query {
+                    let start = final_tokens.windows(2).position(|pair| {
+                        is_punctuation(pair[0], ")") && is_punctuation(pair[1], ".")
+                    });
+
+                    if let Some(start) = start {
+                        chain = get_operators(data, &final_tokens, start + 1);
+                    }
+
+                    if chain
+                        .iter()
+                        .any(|(operator, _)| operator.lambda_variable.is_some())
+                    {
+                        block_syntax = LinqSyntax::Both;
+                    }
+                }
+
+                continue;
+            }
+        }
+
+        if tables.is_empty() {
+            final_data.diagnostics.push(diagnostic(
+                data,
+                DiagnosticCode::UnreadableQuery,
+                Severity::Warning,
+                Some(block.start),
+                "could not find the table the query reads from",
+            ));
+        }
+
+        if where_clauses
+            .iter()
+            .any(|clause| clause.shortcut.is_empty())
+        {
+            final_data.diagnostics.push(diagnostic(
+                data,
+                DiagnosticCode::UnreadableQuery,
+                Severity::Warning,
+                Some(block.start),
+                "could not read a where clause of the query",
+            ));
+        }
+
+        if block_syntax == LinqSyntax::Lambda {
+            projection = chain
+                .iter()
+                .rev()
+                .find(|(operator, _)| operator.kind == OperatorKind::Select)
+                .and_then(|(operator, body)| {
+                    let lambda_variable = operator.lambda_variable.clone()?;
+
+                    Some(get_projection(data, body, &[lambda_variable]))
+                });
+        }
+
+        let return_data = get_return_data(&projection, &tables);
+        let (mut tables, return_data) = get_frequency_and_return_data(&tables, &return_data);
+
+        for index in 0..tables.len() {
+            tables[index].joined_tables = get_joined_tables(&tables, &tables[index].shortcut);
+        }
+
+        get_chain_order_keys(data, &chain, &scope, &mut order_by);
+
+        // `(from ... select new { ... }).OrderBy(t => t.Name)` sorts by what
+        // the query selects, which is a column of one of its tables
+        if block_syntax == LinqSyntax::Both {
+            for order_key in order_by.iter_mut() {
+                order_key.key = get_selected_column(&order_key.key, &tables, &return_data);
+            }
+        }
+
+        let operators = chain
+            .iter()
+            .map(|(operator, _)| operator.clone())
+            .collect::<Vec<LinqOperator>>();
+
+        data_block.details = Some(BlockDetails::SelectBlock {
+            query_type: get_query_type(&operators),
+            skip: get_paging(&operators, OperatorKind::Skip),
+            take: get_paging(&operators, OperatorKind::Take),
+            aggregate: get_aggregate(data, &chain, &scope),
+            projection,
+            order_by,
+            return_data,
+            tables,
+            where_clauses,
+            syntax: block_syntax,
+            has_return,
+            operators,
+        });
+    }
+
+    Data {
+        lines: final_data.lines,
+        blocks: Some(data_blocks),
+        class_name: final_data.class_name,
+        route_prefix: final_data.route_prefix,
+        class_attributes: final_data.class_attributes,
+        tokens: final_data.tokens,
+        line_numbers: final_data.line_numbers,
+        diagnostics: final_data.diagnostics,
+        models: final_data.models,
+        classes: final_data.classes,
+    }
+}
+
+fn get_operator_kind(method: &str) -> OperatorKind {
+    let name = method.strip_suffix("Async").unwrap_or(method);
+
+    match name {
+        "Where" => OperatorKind::Where,
+        "Select" | "SelectMany" => OperatorKind::Select,
+        "OrderBy" => OperatorKind::OrderBy,
+        "OrderByDescending" => OperatorKind::OrderByDescending,
+        "ThenBy" => OperatorKind::ThenBy,
+        "ThenByDescending" => OperatorKind::ThenByDescending,
+        "Skip" => OperatorKind::Skip,
+        "Take" => OperatorKind::Take,
+        "Include" => OperatorKind::Include,
+        "ThenInclude" => OperatorKind::ThenInclude,
+        "Any" => OperatorKind::Any,
+        "Count" | "LongCount" => OperatorKind::Count,
+        "Sum" => OperatorKind::Sum,
+        "Max" => OperatorKind::Max,
+        "Min" => OperatorKind::Min,
+        "Average" => OperatorKind::Average,
+        "Distinct" => OperatorKind::Distinct,
+        "GroupBy" => OperatorKind::GroupBy,
+        name if TERMINAL_OPERATORS.contains(&name) => OperatorKind::Terminal,
+        _ => OperatorKind::Other,
+    }
+}
+
+/// The index of the `.` after `cx.Table` that starts the method chain of a
+/// lambda query.
+fn get_chain_start(blocks: &[Block], block: &Block, tokens: &[&Token]) -> Option<usize> {
+    let variable = get_context_variable(blocks, block)?;
+
+    (0..tokens.len().saturating_sub(3)).find_map(|index| {
+        let starts_chain = index == 0 || !is_punctuation(tokens[index - 1], ".");
+
+        (starts_chain
+            && is_identifier(tokens[index], &variable)
+            && is_punctuation(tokens[index + 1], ".")
+            && tokens[index + 2].kind == TokenKind::Identifier)
+            .then_some(index + 3)
+    })
+}
+
+/// Reads the calls of a method chain from the `.` at `start` on, e.g.
+/// `.Where(t => t.IsDeleted).OrderBy(t => t.Name).ToList()`, each with the
+/// tokens of its lambda body.
+fn get_operators<'a>(
+    data: &Data,
+    tokens: &[&'a Token],
+    start: usize,
+) -> Vec<(LinqOperator, Vec<&'a Token>)> {
+    let mut operators = vec![];
+    let mut index = start;
+
+    while let [dot, method, ..] = &tokens[index.min(tokens.len())..] {
+        if !is_punctuation(dot, ".") || method.kind != TokenKind::Identifier {
+            break;
+        }
+
+        let mut next = index + 2;
+
+        // `.Select<UserDto>(...)`
+        if tokens
+            .get(next)
+            .is_some_and(|token| is_punctuation(token, "<"))
+        {
+            match matching_bracket(tokens, next) {
+                Some(close) => next = close + 1,
+                None => break,
+            }
+        }
+
+        let mut arguments: &[&Token] = &[];
+
+        if tokens
+            .get(next)
+            .is_some_and(|token| is_punctuation(token, "("))
+        {
+            match matching_bracket(tokens, next) {
+                Some(close) => {
+                    arguments = &tokens[next + 1..close];
+                    next = close + 1;
+                }
+                None => break,
+            }
+        }
+
+        let arrow = arguments
+            .iter()
+            .position(|token| is_punctuation(token, "=>"))
+            .filter(|arrow| *arrow > 0);
+
+        let (lambda_variable, body) = match arrow {
+            Some(arrow) => (
+                Some(
+                    source_text(data, arguments[0], arguments[arrow - 1])
+                        .trim_matches(['(', ')'])
+                        .trim()
+                        .to_string(),
+                ),
+                &arguments[arrow + 1..],
+            ),
+            None => (None, arguments),
+        };
+
+        operators.push((
+            LinqOperator {
+                kind: get_operator_kind(&method.text),
+                method: method.text.clone(),
+                lambda_variable,
+                argument: tokens_text(data, body),
+            },
+            body.to_vec(),
+        ));
+
+        index = next;
+    }
+
+    operators
+}
+
+/// The `select new UserTaskDetails()` in front of the braces of a query
+/// syntax block, or the `select` of a query without them.
+fn get_query_projection(data: &Data, block: &Block, shortcuts: &[String]) -> Option<Projection> {
+    let braces = data.lines[block.start as usize]
+        .trim_start()
+        .starts_with('{');
+    let first = if braces {
+        block.start.checked_sub(1)?
+    } else {
+        block.start
+    };
+    let tokens = lines_tokens(data, first, block.end?);
+    let select = tokens
+        .iter()
+        .position(|token| is_keyword(token, "select"))?;
+
+    let end = if braces {
+        let open = select
+            + tokens[select..]
+                .iter()
+                .position(|token| is_punctuation(token, "{"))?;
+
+        matching_bracket(&tokens, open)? + 1
+    } else {
+        get_clause_end(&tokens, select + 1)
+    };
+
+    Some(get_projection(data, &tokens[select + 1..end], shortcuts))
+}
+
+/// Where a clause of a query that starts at `start` ends: at the next query
+/// keyword, the `)` around the query or the end of the statement.
+fn get_clause_end(tokens: &[&Token], start: usize) -> usize {
+    let mut depth = 0;
+
+    for (index, token) in tokens.iter().enumerate().skip(start) {
+        if is_punctuation(token, "(") {
+            depth += 1;
+        } else if is_punctuation(token, ")") {
+            if depth == 0 {
+                return index;
+            }
+
+            depth -= 1;
+        } else if depth == 0
+            && (is_punctuation(token, ";")
+                || (token.kind == TokenKind::Keyword
+                    && QUERY_KEYWORDS.contains(&token.text.as_str())))
+        {
+            return index;
+        }
+    }
+
+    tokens.len()
+}
+
+/// Reads what a query selects, `shortcuts` are the tables and lambda
+/// variables its columns can be read from.
+fn get_projection(data: &Data, tokens: &[&Token], shortcuts: &[String]) -> Projection {
+    let mut tokens = tokens;
+
+    while tokens.len() > 2
+        && is_punctuation(tokens[0], "(")
+        && matching_bracket(tokens, 0) == Some(tokens.len() - 1)
+    {
+        tokens = &tokens[1..tokens.len() - 1];
+    }
+
+    if let [only] = tokens {
+        if shortcuts.contains(&only.text) {
+            return Projection::Row {
+                shortcut: only.text.clone(),
+            };
+        }
+    }
+
+    if let Some(object) = get_projected_object(data, tokens, shortcuts) {
+        return object;
+    }
+
+    if let Some((shortcut, path)) = get_column(tokens, shortcuts) {
+        return Projection::Column { shortcut, path };
+    }
+
+    if let Some(collection) = get_projected_collection(data, tokens, shortcuts) {
+        return collection;
+    }
+
+    let (columns, properties) = member_accesses(tokens);
+    let (shortcuts, properties) = columns
+        .into_iter()
+        .zip(properties)
+        .filter(|(shortcut, _)| shortcuts.contains(shortcut))
+        .unzip();
+
+    Projection::Computed {
+        expression: tokens_text(data, tokens),
+        shortcuts,
+        properties,
+    }
+}
+
+/// `ut.Name` or `ut.User.Name` split into the shortcut and the path.
+fn get_column(tokens: &[&Token], shortcuts: &[String]) -> Option<(String, Vec<String>)> {
+    let (first, rest) = tokens.split_first()?;
+
+    if first.kind != TokenKind::Identifier || !shortcuts.contains(&first.text) || rest.is_empty() {
+        return None;
+    }
+
+    rest.chunks(2)
+        .map(|pair| match pair {
+            [dot, name] if is_punctuation(dot, ".") && name.kind == TokenKind::Identifier => {
+                Some(name.text.clone())
+            }
+            _ => None,
+        })
+        .collect::<Option<Vec<String>>>()
+        .map(|path| (first.text.clone(), path))
+}
+
+/// `new UserDto() { Name = u.Name }` or the anonymous `new { u.Name }`.
+fn get_projected_object(
+    data: &Data,
+    tokens: &[&Token],
+    shortcuts: &[String],
+) -> Option<Projection> {
+    if !is_keyword(tokens.first()?, "new") {
+        return None;
+    }
+
+    let open = tokens.iter().position(|token| is_punctuation(token, "{"))?;
+
+    if matching_bracket(tokens, open)? != tokens.len() - 1 {
+        return None;
+    }
 
-                if !has_comma && !has_end {
-                    previous_type = prev_line[next_index].to_string();
-                } else {
-                    variables.push(Variable {
-                        name: prev_line[next_index].replace(",", "").replace(")", ""),
-                        variable_type: previous_type.clone(),
-                    });
+    let type_tokens = match &tokens[1..open] {
+        [rest @ .., open, close] if is_punctuation(open, "(") && is_punctuation(close, ")") => rest,
+        type_tokens => type_tokens,
+    };
+
+    let fields = split_on_commas(&tokens[open + 1..tokens.len() - 1])
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .map(|part| match part.as_slice() {
+            [name, equals, value @ ..]
+                if name.kind == TokenKind::Identifier
+                    && is_punctuation(equals, "=")
+                    && !value.is_empty() =>
+            {
+                ProjectedField {
+                    name: name.text.clone(),
+                    value: get_projection(data, value, shortcuts),
                 }
-
-                next_index += 1;
             }
+            _ => ProjectedField {
+                name: part
+                    .iter()
+                    .rev()
+                    .find(|token| token.kind == TokenKind::Identifier)
+                    .map(|token| token.text.clone())
+                    .unwrap_or_default(),
+                value: get_projection(data, &part, shortcuts),
+            },
+        })
+        .collect();
 
-            block.details = Some(BlockDetails::MethodBlock {
-                name: function_name,
-                variables,
-                http_method: determine_http_method(&data, &block),
-                uses_context: data_blocks.iter().any(|b| {
-                    b.start > block.start
-                        && b.end.unwrap() < block.end.unwrap()
-                        && b.block_type == BlockType::Context
-                }),
-            });
-
-            data_blocks[index] = block;
-        } else if block.block_type == BlockType::Variable {
-            let parts = new_data.lines[block.start as usize - 1]
-                .trim_start()
-                .split(" ")
-                .collect::<Vec<&str>>();
-
-            let name = parts[1].to_string();
-            let data_type = if parts[2..].contains(&"new") {
-                let correct_data_type =
-                    parts[2..].iter().position(|a| *a == "new").unwrap() + 1 + 2;
-                parts[correct_data_type].replace("()", "")
-            } else {
-                parts[0].to_string()
-            };
+    Some(Projection::Object {
+        type_name: (!type_tokens.is_empty()).then(|| tokens_text(data, type_tokens)),
+        fields,
+    })
+}
 
-            block.details = Some(BlockDetails::VariableBlock { name, data_type });
-            data_blocks[index] = block;
-        } else if block.block_type == BlockType::If {
-            let prev_line = &new_data.lines[block.start as usize - 1].trim_start();
+/// `u.Tasks.Where(...).Select(t => ...).ToList()`, a chain that keeps a list
+/// of items. Chains ending in `First()`, `Count()` and the like are computed.
+fn get_projected_collection(
+    data: &Data,
+    tokens: &[&Token],
+    shortcuts: &[String],
+) -> Option<Projection> {
+    let first = tokens.first()?;
+
+    if first.kind != TokenKind::Identifier
+        || !shortcuts.contains(&first.text)
+        || !is_punctuation(tokens.last()?, ")")
+    {
+        return None;
+    }
 
-            let first_four_chars = if prev_line.len() >= 4 {
-                &prev_line[0..4]
-            } else {
-                ""
-            };
+    let mut path = vec![];
+    let mut index = 1;
 
-            let has_else = first_four_chars == "else";
+    while index + 1 < tokens.len()
+        && is_punctuation(tokens[index], ".")
+        && tokens[index + 1].kind == TokenKind::Identifier
+        && !tokens
+            .get(index + 2)
+            .is_some_and(|token| is_punctuation(token, "(") || is_punctuation(token, "<"))
+    {
+        path.push(tokens[index + 1].text.clone());
+        index += 2;
+    }
 
-            block.details = Some(BlockDetails::IfBlock {
-                clause: if has_else && !prev_line.contains("if") {
-                    "".to_string()
-                } else {
-                    let splits = prev_line.trim_start().split("(").collect::<Vec<&str>>();
-                    splits[1][0..(splits[1].len() - 1)].to_string()
-                },
-                is_else: has_else,
-            });
-            data_blocks[index] = block;
+    let chain = get_operators(data, tokens, index);
+
+    let keeps_items = |operator: &LinqOperator| match operator.kind {
+        OperatorKind::Where
+        | OperatorKind::Select
+        | OperatorKind::OrderBy
+        | OperatorKind::OrderByDescending
+        | OperatorKind::ThenBy
+        | OperatorKind::ThenByDescending
+        | OperatorKind::Skip
+        | OperatorKind::Take
+        | OperatorKind::Distinct => true,
+        OperatorKind::Terminal => {
+            operator.method.starts_with("To") || operator.method == "AsEnumerable"
         }
-    }
+        _ => false,
+    };
 
-    Data {
-        lines: new_data.lines,
-        blocks: Some(data_blocks),
-        class_name: new_data.class_name,
+    if path.is_empty()
+        || chain.is_empty()
+        || !chain.iter().all(|(operator, _)| keeps_items(operator))
+    {
+        return None;
     }
+
+    let element = chain
+        .iter()
+        .rev()
+        .find(|(operator, _)| operator.kind == OperatorKind::Select)
+        .and_then(|(operator, body)| {
+            let lambda_variable = operator.lambda_variable.clone()?;
+            let shortcuts = [shortcuts, &[lambda_variable]].concat();
+
+            Some(Box::new(get_projection(data, body, &shortcuts)))
+        });
+
+    Some(Projection::Collection {
+        shortcut: first.text.clone(),
+        path,
+        operators: chain.into_iter().map(|(operator, _)| operator).collect(),
+        element,
+    })
 }
 
-fn get_frequency_and_return_data(
-    tables: &Vec<Table>,
-    return_data: &Vec<ReturnData>,
-) -> (Vec<Table>, Vec<ReturnData>) {
-    let mut tables = tables.clone();
-    let mut map: HashMap<String, i32> = HashMap::new();
+/// The columns of the selected object the Prisma select is written from,
+/// computed values keep their expression and have no table.
+fn get_return_data(projection: &Option<Projection>, tables: &[Table]) -> Vec<ReturnData> {
+    let fields = match projection {
+        Some(Projection::Object { fields, .. }) => fields,
+        _ => return vec![],
+    };
 
-    for data in return_data.clone() {
-        let old_value = map.get(&data.table).unwrap_or(&0);
-        map.insert(data.table, *old_value + 1);
+    let table_name = |shortcut: &str| {
+        tables
+            .iter()
+            .find(|table| table.shortcut == shortcut)
+            .map(|table| table.name.clone())
+            .unwrap_or_default()
+    };
+
+    fields
+        .iter()
+        .filter_map(|field| match &field.value {
+            Projection::Column { shortcut, path } if path.len() == 1 => Some(ReturnData {
+                table: table_name(shortcut),
+                property: field.name.clone(),
+                value: path[0].clone(),
+            }),
+            Projection::Column { shortcut, path } => Some(ReturnData {
+                table: String::new(),
+                property: field.name.clone(),
+                value: format!("{}.{}", shortcut, path.join(".")),
+            }),
+            Projection::Computed { expression, .. } => Some(ReturnData {
+                table: String::new(),
+                property: field.name.clone(),
+                value: expression.clone(),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// `First()` and `Single()` throw when no row is found, the `OrDefault`
+/// variants return null.
+fn get_query_type(operators: &[LinqOperator]) -> QueryType {
+    let terminal = operators
+        .iter()
+        .rev()
+        .find(|operator| operator.kind == OperatorKind::Terminal)
+        .map(|operator| operator.method.trim_end_matches("Async"));
+
+    match terminal {
+        Some("First") => QueryType::FirstOrThrow,
+        Some("FirstOrDefault") => QueryType::First,
+        Some("Single") => QueryType::UniqueOrThrow,
+        Some("SingleOrDefault") => QueryType::Unique,
+        _ => QueryType::Many,
     }
+}
 
-    tables.iter_mut().for_each(|table| {
-        table.return_frequency = map.get(&table.name).unwrap_or(&0).to_owned();
-    });
+/// The argument of the last `.Skip(...)` or `.Take(...)`.
+fn get_paging(operators: &[LinqOperator], kind: OperatorKind) -> Option<String> {
+    operators
+        .iter()
+        .rev()
+        .find(|operator| operator.kind == kind && !operator.argument.is_empty())
+        .map(|operator| operator.argument.clone())
+}
 
-    let mut return_data = return_data.clone();
-    return_data.sort_by(|a, b| {
-        map.get(&b.table)
-            .unwrap_or(&0)
-            .cmp(map.get(&a.table).unwrap_or(&0))
-    });
+/// Reads `orderby ut.DueDate descending, ut.Name`.
+fn get_query_order_keys(
+    data: &Data,
+    tokens: &[&Token],
+    shortcuts: &[String],
+    scope: &Scope,
+) -> Vec<OrderKey> {
+    split_on_commas(tokens)
+        .into_iter()
+        .filter_map(|key| {
+            let (direction, key) = match key.split_last()? {
+                (last, rest) if is_keyword(last, "descending") => (SortDirection::Descending, rest),
+                (last, rest) if is_keyword(last, "ascending") => (SortDirection::Ascending, rest),
+                _ => (SortDirection::Ascending, key.as_slice()),
+            };
 
-    (tables, return_data)
+            if key.is_empty() {
+                return None;
+            }
+
+            Some(OrderKey {
+                key: get_expression(data, key, shortcuts, scope),
+                direction,
+            })
+        })
+        .collect()
 }
 
-fn attach_select_block_details(data: &Data) -> Data {
-    let final_data = data.clone();
-    let new_data = data.clone();
-    let mut data_blocks = new_data.blocks.unwrap();
+/// Adds the keys of `.OrderBy(...)` and the `ThenBy` calls after it, a later
+/// `OrderBy` sorts the rows again and replaces the keys before it.
+fn get_chain_order_keys(
+    data: &Data,
+    chain: &[(LinqOperator, Vec<&Token>)],
+    scope: &Scope,
+    order_by: &mut Vec<OrderKey>,
+) {
+    for (operator, body) in chain {
+        let direction = match operator.kind {
+            OperatorKind::OrderBy | OperatorKind::ThenBy => SortDirection::Ascending,
+            OperatorKind::OrderByDescending | OperatorKind::ThenByDescending => {
+                SortDirection::Descending
+            }
+            _ => continue,
+        };
 
-    for index in 0..data_blocks.len() {
-        let block = data_blocks[index].clone();
-        if block.block_type != BlockType::Select {
+        if body.is_empty() || operator.lambda_variable.is_none() {
             continue;
         }
 
-        let mut block_syntax = LinqSyntax::Query;
-        let mut where_clauses = vec![];
-        let mut tables = vec![];
-        let mut has_return = false;
-        let mut query_type = QueryType::Many;
-        let mut return_data = vec![];
-
-        for index in block.start..=block.end.unwrap() {
-            let line = &data.lines[index as usize];
-
-            if index == block.start {
-                if line.contains(".Where") {
-                    block_syntax = LinqSyntax::Lambda;
-                }
-
-                if block_syntax == LinqSyntax::Query {
-                    let keywords = ["from", "join", "where", "select", "&&"];
+        if matches!(
+            operator.kind,
+            OperatorKind::OrderBy | OperatorKind::OrderByDescending
+        ) {
+            order_by.clear();
+        }
 
-                    let mut current_index = (index - 1) as usize;
-                    while keywords
-                        .iter()
-                        .any(|word| data.lines[current_index].contains(word))
-                    {
-                        let line = &data.lines[current_index].trim_start();
-                        if line.contains("where") || line.contains("&&") {
-                            let parts = line.clone().split(" ").collect::<Vec<&str>>();
+        order_by.push(OrderKey {
+            key: get_expression(data, body, operator.lambda_variable.as_slice(), scope),
+            direction,
+        });
+    }
+}
 
-                            let properties = parts
-                                .iter()
-                                .filter(|o| o.contains("."))
-                                .collect::<Vec<&&str>>();
+/// The column a property of the selected object is read from, other keys
+/// are kept as they are.
+fn get_selected_column(
+    key: &Expression,
+    tables: &[Table],
+    return_data: &[ReturnData],
+) -> Expression {
+    let property = match key {
+        Expression::Column { path, .. } if path.len() == 1 => &path[0],
+        _ => return key.clone(),
+    };
+
+    return_data
+        .iter()
+        .find(|data| data.property == *property)
+        .and_then(|data| {
+            let table = tables.iter().find(|table| table.name == data.table)?;
+
+            Some(Expression::Column {
+                shortcut: table.shortcut.clone(),
+                path: vec![data.value.clone()],
+            })
+        })
+        .unwrap_or_else(|| key.clone())
+}
 
-                            let mut local_shortcut = vec![];
-                            let mut local_property = vec![];
-                            for property in properties {
-                                let parts = property.split(".").collect::<Vec<&str>>();
+/// The aggregate the chain ends with, `.Sum(t => t.Hours)` or
+/// `.Select(t => t.Hours).Sum()`.
+fn get_aggregate(
+    data: &Data,
+    chain: &[(LinqOperator, Vec<&Token>)],
+    scope: &Scope,
+) -> Option<Aggregate> {
+    let last = chain
+        .iter()
+        .rposition(|(operator, _)| operator.kind != OperatorKind::Other)?;
+    let (operator, body) = &chain[last];
+
+    let kind = match operator.kind {
+        OperatorKind::Count => AggregateKind::Count,
+        OperatorKind::Any => AggregateKind::Any,
+        OperatorKind::Sum => AggregateKind::Sum,
+        OperatorKind::Max => AggregateKind::Max,
+        OperatorKind::Min => AggregateKind::Min,
+        OperatorKind::Average => AggregateKind::Average,
+        _ => return None,
+    };
+
+    let selector = if matches!(kind, AggregateKind::Count | AggregateKind::Any) {
+        None
+    } else if operator.lambda_variable.is_some() && !body.is_empty() {
+        Some(get_expression(
+            data,
+            body,
+            operator.lambda_variable.as_slice(),
+            scope,
+        ))
+    } else {
+        chain[..last]
+            .iter()
+            .rev()
+            .find(|(operator, body)| {
+                operator.kind == OperatorKind::Select
+                    && operator.lambda_variable.is_some()
+                    && !body.is_empty()
+            })
+            .map(|(operator, body)| {
+                get_expression(data, body, operator.lambda_variable.as_slice(), scope)
+            })
+    };
 
-                                let shortcut = parts[0].to_string();
-                                let property = parts[1].to_string();
+    Some(Aggregate { kind, selector })
+}
 
-                                local_shortcut.push(shortcut);
-                                local_property.push(property);
-                            }
+/// The filter of an operator that takes a predicate, like `.Where(...)`,
+/// `.Any(...)` or `.FirstOrDefault(...)`.
+fn get_operator_filter(
+    data: &Data,
+    operator: &LinqOperator,
+    body: &[&Token],
+    scope: &Scope,
+) -> Option<WhereClause> {
+    let takes_predicate = match operator.kind {
+        OperatorKind::Where | OperatorKind::Any | OperatorKind::Count => true,
+        OperatorKind::Terminal => {
+            operator.method.starts_with("First")
+                || operator.method.starts_with("Single")
+                || operator.method.starts_with("Last")
+        }
+        _ => false,
+    };
 
-                            if local_shortcut.len() > 0 {
-                                where_clauses.push(WhereClause {
-                                    shortcut: local_shortcut,
-                                    property: local_property,
-                                    value: data.lines[current_index].trim_start().to_string(),
-                                    lambda_varible: None,
-                                });
-                            }
+    if !takes_predicate || operator.lambda_variable.is_none() {
+        return None;
+    }
 
-                            current_index -= 1;
-                            continue;
-                        }
+    let (shortcut, property) = member_accesses(body);
 
-                        if data.lines[current_index].contains("join") {
-                            let keyword_index = data.lines[current_index].find("join").unwrap();
-                            let keyword_string =
-                                data.lines[current_index][keyword_index..].to_string();
+    if shortcut.is_empty() {
+        return Some(unread_filter(
+            operator.argument.clone(),
+            operator.lambda_variable.clone(),
+        ));
+    }
 
-                            let parts = keyword_string.split(" ").collect::<Vec<&str>>();
+    Some(WhereClause {
+        shortcut,
+        property,
+        value: operator.argument.clone(),
+        lambda_varible: operator.lambda_variable.clone(),
+        expression: Some(get_expression(
+            data,
+            body,
+            operator.lambda_variable.as_slice(),
+            scope,
+        )),
+    })
+}
 
-                            let shortcut = parts[1].to_string();
-                            let table_name = parts[3].to_string().replace("cx.", "");
+/// A filter without a column that could be read. It is kept as written so
+/// the query is not run without it.
+fn unread_filter(value: String, lambda_variable: Option<String>) -> WhereClause {
+    WhereClause {
+        shortcut: vec![],
+        property: vec![],
+        expression: Some(Expression::Raw {
+            text: value.clone(),
+        }),
+        value,
+        lambda_varible: lambda_variable,
+    }
+}
 
-                            tables.push(Table {
-                                name: table_name,
-                                shortcut,
-                                joined_tables: vec![],
-                                return_frequency: 0,
-                            });
+/// The name `var userTask = ...` or `userTask = ...` assigns to.
+fn get_assigned_name(tokens: &[&Token]) -> Option<String> {
+    let equals = tokens.iter().position(|token| is_punctuation(token, "="))?;
+    let name = tokens.get(equals.checked_sub(1)?)?;
 
-                            current_index -= 1;
-                            continue;
-                        }
+    if name.kind != TokenKind::Identifier || (equals > 1 && is_punctuation(tokens[equals - 2], "."))
+    {
+        return None;
+    }
 
-                        if data.lines[current_index].contains("from") {
-                            let keyword_index = data.lines[current_index].find("from").unwrap();
-                            let keyword_string =
-                                data.lines[current_index][keyword_index..].to_string();
-                            let parts = keyword_string.split(" ").collect::<Vec<&str>>();
+    Some(name.text.clone())
+}
 
-                            let shortcut = parts[1].to_string();
-                            let table_name = parts[3].to_string().replace("cx.", "");
+/// `userTask.CompleteDate = DateTime.UtcNow;` split into the variable and the
+/// assignment.
+fn get_member_assignment(
+    data: &Data,
+    tokens: &[&Token],
+    scope: &Scope,
+) -> Option<(String, Assignment)> {
+    let tokens = match tokens.last() {
+        Some(last) if is_punctuation(last, ";") => &tokens[..tokens.len() - 1],
+        _ => tokens,
+    };
+
+    match tokens {
+        [variable, dot, rest @ ..]
+            if variable.kind == TokenKind::Identifier && is_punctuation(dot, ".") =>
+        {
+            Some((variable.text.clone(), get_assignment(data, rest, scope)?))
+        }
+        _ => None,
+    }
+}
 
-                            tables.push(Table {
-                                name: table_name,
-                                shortcut,
-                                joined_tables: vec![],
-                                return_frequency: 0,
-                            });
+/// The variable a select block loads its result into. Query syntax starts on
+/// the lines above the opening brace.
+fn get_select_variable(data: &Data, block: &Block) -> Option<String> {
+    let mut line = block.start as usize;
 
-                            if data.lines[current_index].contains("return ") {
-                                has_return = true;
-                            }
+    if let Some(name) = get_assigned_name(&line_tokens(data, line)) {
+        return Some(name);
+    }
 
-                            current_index -= 1;
-                            continue;
-                        }
+    if !data.lines[line].trim_start().starts_with('{') {
+        return None;
+    }
 
-                        current_index -= 1;
-                    }
-                }
+    while line > 0 && is_query_line(&line_tokens(data, line - 1)) {
+        line -= 1;
 
-                if block_syntax == LinqSyntax::Lambda {
-                    let start_sub = line.find(".Where(").unwrap() + 7;
-                    let end_sub = line.find(")").unwrap();
+        if let Some(name) = get_assigned_name(&line_tokens(data, line)) {
+            return Some(name);
+        }
+    }
 
-                    let split_values = line[start_sub..end_sub].split("=>").collect::<Vec<&str>>();
+    None
+}
 
-                    let lambda_varible = Some(split_values[0].trim().to_string());
-                    let value = split_values[1].trim().to_string();
+/// The entities the select blocks of a context load into variables, with the
+/// table and filters they were read with. Changing their properties and
+/// saving updates them.
+fn get_tracked_entities(
+    data: &Data,
+    blocks: &[Block],
+    context: &Block,
+) -> HashMap<String, (String, Vec<WhereClause>)> {
+    let mut entities = HashMap::new();
+
+    for block in blocks.iter().filter(|block| {
+        block.block_type == BlockType::Select
+            && block.start > context.start
+            && block.end <= context.end
+    }) {
+        let (tables, where_clauses) = match &block.details {
+            Some(BlockDetails::SelectBlock {
+                tables,
+                where_clauses,
+                ..
+            }) => (tables, where_clauses),
+            _ => continue,
+        };
 
-                    let properties = value.split(" ").filter(|o| o.contains("."));
+        let max_frequency = tables.iter().map(|table| table.return_frequency).max();
+        let table = tables
+            .iter()
+            .find(|table| Some(table.return_frequency) == max_frequency);
 
-                    let mut shortcut = vec![];
-                    let mut property = vec![];
-                    for prop in properties {
-                        let parts = prop.split(".").collect::<Vec<&str>>();
-                        shortcut.push(parts[0].to_string());
-                        property.push(parts[1].to_string());
-                    }
+        if let (Some(name), Some(table)) = (get_select_variable(data, block), table) {
+            entities.insert(name, (table.name.clone(), where_clauses.clone()));
+        }
+    }
 
-                    if shortcut.len() > 0 {
-                        where_clauses.push(WhereClause {
-                            shortcut,
-                            property,
-                            value,
-                            lambda_varible,
-                        });
-                    }
+    entities
+}
 
-                    let words = line.split(" ").filter(|o| o.contains("cx."));
+/// Reads `cx.Users.Add(user)` and `cx.SaveChanges()` into the table, the
+/// write operation and its argument.
+fn get_context_write(
+    data: &Data,
+    tokens: &[&Token],
+    variable: &str,
+) -> Option<(Option<String>, String, String)> {
+    let start = (0..tokens.len().saturating_sub(2)).find(|index| {
+        is_identifier(tokens[*index], variable)
+            && is_punctuation(tokens[index + 1], ".")
+            && (*index == 0 || !is_punctuation(tokens[index - 1], "."))
+    })?;
+
+    let (table, operation) = if is_write_operation(tokens, start + 2) {
+        (None, start + 2)
+    } else if start + 4 < tokens.len()
+        && tokens[start + 2].kind == TokenKind::Identifier
+        && is_write_operation(tokens, start + 4)
+    {
+        (Some(tokens[start + 2].text.clone()), start + 4)
+    } else {
+        return None;
+    };
 
-                    for word in words {
-                        let parts = word.split(".").collect::<Vec<&str>>();
+    let close = matching_bracket(tokens, operation + 1)?;
+    let argument = if close > operation + 2 {
+        source_text(data, tokens[operation + 2], tokens[close - 1])
+    } else {
+        String::new()
+    };
 
-                        tables.push(Table {
-                            name: parts[1].to_string(),
-                            shortcut: parts[0].to_string(),
-                            joined_tables: vec![],
-                            return_frequency: 0,
-                        });
-                    }
+    Some((table, tokens[operation].text.clone(), argument))
+}
 
-                    if line.contains("return ") {
-                        has_return = true;
-                    }
-                }
+/// The type and object initializer of the `var name = new Type() { ... }`
+/// block in front of a line.
+fn get_initializer(blocks: &[Block], name: &str, line: u32) -> Option<(String, Vec<Assignment>)> {
+    blocks.iter().rev().find_map(|block| match &block.details {
+        Some(BlockDetails::VariableBlock {
+            name: variable,
+            data_type,
+            assignments,
+        }) if variable == name && block.end.is_some_and(|end| end < line) => {
+            Some((data_type.clone(), assignments.clone()))
+        }
+        _ => None,
+    })
+}
 
-                if block.start != block.end.unwrap() {
-                    continue;
-                }
-            }
+/// Inserts, deletes and updates of tracked entities written on a database
+/// context, each a single statement block like the lambda selects. Updates
+/// are the property assignments in front of a `SaveChanges` call.
+fn get_write_blocks(data: &Data) -> Data {
+    let data = data.clone();
+    let data_blocks = data.blocks.clone().unwrap();
 
-            if index == block.end.unwrap() {
-                let final_line = if line.contains(")") {
-                    line
-                } else {
-                    &data.lines[index as usize + 1]
-                };
+    let mut result: Vec<Block> = vec![];
+    for block in &data_blocks {
+        let variable = match &block.details {
+            Some(BlockDetails::ContextBlock { variable }) => variable,
+            _ => continue,
+        };
 
-                if final_line.contains(".SingleOrDefault") {
-                    query_type = QueryType::Unique;
-                }
+        let tracked = get_tracked_entities(&data, &data_blocks, block);
+        let last_save = (block.start + 1..block.end.unwrap()).rev().find(|line| {
+            get_context_write(&data, &line_tokens(&data, *line as usize), variable)
+                .is_some_and(|(_, operation, _)| operation.starts_with("SaveChanges"))
+        });
 
-                if final_line.contains(".FirstOrDefault") {
-                    query_type = QueryType::First;
-                }
+        let mut update: Option<Block> = None;
+        for i in block.start + 1..block.end.unwrap() {
+            let tokens = line_tokens(&data, i as usize);
 
+            if tokens.is_empty() {
                 continue;
             }
 
-            let trimmed_line = line.trim_start().replace(",", "");
-            let parts = trimmed_line.split(" ").collect::<Vec<&str>>();
-
-            let property = parts[0].to_string();
-            let value = parts[2..].join(" ");
-
-            let mut shortcut = None;
-            let mut value = value;
+            let scope = get_scope(&data, &data_blocks, i);
+
+            if let Some((target, assignment)) = get_member_assignment(&data, &tokens, &scope) {
+                if let (Some((table, where_clauses)), Some(true)) =
+                    (tracked.get(&target), last_save.map(|save| i < save))
+                {
+                    if let Some(Block {
+                        end: Some(end),
+                        details:
+                            Some(BlockDetails::UpdateBlock {
+                                variable,
+                                assignments,
+                                ..
+                            }),
+                        ..
+                    }) = &mut update
+                    {
+                        if *variable == target {
+                            *end = i;
+                            assignments.push(assignment);
+                            continue;
+                        }
+                    }
 
-            if value.contains(".") {
-                let parts = value.split(".").collect::<Vec<&str>>();
-                shortcut = Some(parts[0].to_string());
-                value = parts[1].to_string();
+                    result.extend(update.take());
+                    update = Some(Block {
+                        start: i,
+                        end: Some(i),
+                        block_type: BlockType::Write,
+                        details: Some(BlockDetails::UpdateBlock {
+                            table: table.clone(),
+                            variable: target,
+                            where_clauses: where_clauses.clone(),
+                            assignments: vec![assignment],
+                        }),
+                    });
+                    continue;
+                }
             }
 
-            let mut table = "".to_string();
+            result.extend(update.take());
 
-            if shortcut.is_some() {
-                let shortcut = shortcut.unwrap();
-                let correct_table = tables.iter().find(|o| o.shortcut == shortcut);
+            let (table, operation, argument) = match get_context_write(&data, &tokens, variable) {
+                Some(write) => write,
+                None => continue,
+            };
+
+            let details = match operation.as_str() {
+                "Add" | "AddAsync" | "AddRange" => {
+                    let (data_type, assignments) =
+                        get_initializer(&data_blocks, &argument, i).unwrap_or_default();
 
-                if let Some(correct_table) = correct_table {
-                    table = correct_table.name.clone();
+                    BlockDetails::InsertBlock {
+                        table: table.unwrap_or(data_type),
+                        variable: argument,
+                        assignments,
+                        many: operation == "AddRange",
+                    }
                 }
-            }
+                "Remove" | "RemoveRange" => {
+                    let (tracked_table, where_clauses) =
+                        tracked.get(&argument).cloned().unwrap_or_default();
+
+                    BlockDetails::DeleteBlock {
+                        table: table.unwrap_or(tracked_table),
+                        variable: argument,
+                        where_clauses,
+                        many: operation == "RemoveRange",
+                    }
+                }
+                _ => BlockDetails::SaveChangesBlock {
+                    variable: variable.clone(),
+                },
+            };
 
-            return_data.push(ReturnData {
-                property,
-                table,
-                value,
+            result.push(Block {
+                start: i,
+                end: Some(i),
+                block_type: BlockType::Write,
+                details: Some(details),
             });
         }
 
-        let (tables, return_data) = get_frequency_and_return_data(&tables, &return_data);
-
-        data_blocks[index].details = Some(BlockDetails::SelectBlock {
-            query_type,
-            return_data,
-            tables,
-            where_clauses,
-            syntax: block_syntax,
-            has_return,
-        });
+        result.extend(update.take());
     }
 
     Data {
-        lines: final_data.lines,
-        blocks: Some(data_blocks),
-        class_name: final_data.class_name,
+        lines: data.lines,
+        blocks: Some([data_blocks, result].concat()),
+        class_name: data.class_name,
+        route_prefix: data.route_prefix,
+        class_attributes: data.class_attributes,
+        tokens: data.tokens,
+        line_numbers: data.line_numbers,
+        diagnostics: data.diagnostics,
+        models: data.models,
+        classes: data.classes,
     }
 }
 
 #[cfg(test)]
-mod tests {
+#[allow(clippy::bool_assert_comparison, clippy::assertions_on_constants)]
+pub(crate) mod tests {
     use super::analyze_lines;
-    use crate::{Block, BlockDetails, BlockType, Data, HttpType, LinqSyntax, QueryType};
+    use crate::{
+        Aggregate, AggregateKind, Assignment, Block, BlockDetails, BlockType, Data, DiagnosticCode,
+        Expression, HttpType, Join, JoinKey, JoinKind, LinqOperator, LinqSyntax, Member,
+        MemberKind, OperatorKind, OrderKey, ProjectedField, Projection, QueryType, Reference,
+        Severity, SortDirection,
+    };
+
+    /// Analyzes a mock controller the way the CLI does.
+    pub(crate) fn analyze_file(path: &str) -> Data {
+        let input = std::fs::read_to_string(path).expect("Something went wrong reading the file");
+
+        crate::analyze_source(&input).data
+    }
 
     #[test]
     fn analyze_data_input_1() {
@@ -725,6 +3177,13 @@ mod tests {
             lines: lines.iter().map(|x| x.to_string()).collect::<Vec<String>>(),
             class_name: None,
             blocks: None,
+            route_prefix: None,
+            class_attributes: vec![],
+            tokens: vec![],
+            line_numbers: vec![],
+            diagnostics: vec![],
+            models: vec![],
+            classes: vec![],
         };
 
         data = analyze_lines(data);
@@ -733,8 +3192,16 @@ mod tests {
         let parent_data = data.clone();
         assert!(parent_data.class_name.is_some());
         assert_eq!(parent_data.class_name.unwrap(), "TestController");
+        assert_eq!(
+            parent_data.route_prefix,
+            Some("api/[controller]".to_string())
+        );
+        assert_eq!(
+            parent_data.class_attributes,
+            vec!["Route(\"api/[controller]\")", "ApiController"]
+        );
         assert!(parent_data.blocks.is_some());
-        assert_eq!(parent_data.blocks.unwrap().len(), 33);
+        assert_eq!(parent_data.blocks.unwrap().len(), 44);
 
         // method blocks
         let method_blocks = data.clone().blocks.unwrap();
@@ -751,6 +3218,7 @@ mod tests {
                 Some(BlockDetails::MethodBlock {
                     http_method,
                     name: _,
+                    route: _,
                     variables: _,
                     uses_context: _,
                 }) => http_method.is_none(),
@@ -769,11 +3237,13 @@ mod tests {
         if let BlockDetails::MethodBlock {
             name,
             http_method,
+            route,
             variables,
             uses_context,
         } = details
         {
             assert_eq!(name, "UpdateUserTask");
+            assert_eq!(route, None);
             assert_eq!(uses_context, true);
             assert_eq!(variables.len(), 1);
             assert_eq!(http_method, None);
@@ -792,11 +3262,13 @@ mod tests {
         if let BlockDetails::MethodBlock {
             name,
             http_method,
+            route,
             variables,
             uses_context,
         } = details
         {
             assert_eq!(name, "AddUserTask");
+            assert_eq!(route, None);
             assert_eq!(uses_context, true);
             assert_eq!(variables.len(), 1);
             assert_eq!(http_method, None);
@@ -813,6 +3285,7 @@ mod tests {
                 Some(BlockDetails::MethodBlock {
                     http_method,
                     name: _,
+                    route: _,
                     variables: _,
                     uses_context: _,
                 }) => http_method.is_some(),
@@ -828,6 +3301,7 @@ mod tests {
                 Some(BlockDetails::MethodBlock {
                     http_method,
                     name: _,
+                    route: _,
                     variables: _,
                     uses_context: _,
                 }) => http_method.clone().unwrap() == HttpType::HttpGet,
@@ -844,11 +3318,16 @@ mod tests {
         if let BlockDetails::MethodBlock {
             name,
             http_method,
+            route,
             variables,
             uses_context,
         } = details
         {
             assert_eq!(name, "getClientTodoTasks");
+            assert_eq!(
+                route.map(|route| route.path),
+                Some("/api/Test/todoUserTasks/{userOid}".to_string())
+            );
             assert_eq!(uses_context, true);
             assert_eq!(variables.len(), 1);
             assert_eq!(http_method, Some(HttpType::HttpGet));
@@ -867,11 +3346,16 @@ mod tests {
         if let BlockDetails::MethodBlock {
             name,
             http_method,
+            route,
             variables,
             uses_context,
         } = details
         {
             assert_eq!(name, "GetUserTaskDetails");
+            assert_eq!(
+                route.map(|route| route.path),
+                Some("/api/Test/userTaskDetails/{userTaskOid}".to_string())
+            );
             assert_eq!(uses_context, true);
             assert_eq!(variables.len(), 1);
             assert_eq!(http_method, Some(HttpType::HttpGet));
@@ -887,6 +3371,7 @@ mod tests {
                 Some(BlockDetails::MethodBlock {
                     http_method,
                     name: _,
+                    route: _,
                     variables: _,
                     uses_context: _,
                 }) => http_method.clone().unwrap() == HttpType::HttpPost,
@@ -903,11 +3388,16 @@ mod tests {
         if let BlockDetails::MethodBlock {
             name,
             http_method,
+            route,
             variables,
             uses_context,
         } = details
         {
             assert_eq!(name, "AddAdmin");
+            assert_eq!(
+                route.map(|route| (route.template, route.path)),
+                Some(("".to_string(), "/api/Test".to_string()))
+            );
             assert_eq!(uses_context, true);
             assert_eq!(variables.len(), 1);
             assert_eq!(http_method, Some(HttpType::HttpPost));
@@ -926,11 +3416,16 @@ mod tests {
         if let BlockDetails::MethodBlock {
             name,
             http_method,
+            route,
             variables,
             uses_context,
         } = details
         {
             assert_eq!(name, "AddUpdateUserTask");
+            assert_eq!(
+                route.map(|route| route.path),
+                Some("/api/Test/addUpdateUserTask".to_string())
+            );
             assert_eq!(uses_context, false);
             assert_eq!(variables.len(), 1);
             assert_eq!(http_method, Some(HttpType::HttpPost));
@@ -946,6 +3441,7 @@ mod tests {
                 Some(BlockDetails::MethodBlock {
                     http_method,
                     name: _,
+                    route: _,
                     variables: _,
                     uses_context: _,
                 }) => http_method.clone().unwrap() == HttpType::HttpPut,
@@ -962,11 +3458,16 @@ mod tests {
         if let BlockDetails::MethodBlock {
             name,
             http_method,
+            route,
             variables,
             uses_context,
         } = details
         {
             assert_eq!(name, "CompleteTask");
+            assert_eq!(
+                route.map(|route| route.path),
+                Some("/api/Test/completeUserTask/{userTaskOid}".to_string())
+            );
             assert_eq!(uses_context, true);
             assert_eq!(variables.len(), 1);
             assert_eq!(http_method, Some(HttpType::HttpPut));
@@ -982,6 +3483,7 @@ mod tests {
                 Some(BlockDetails::MethodBlock {
                     http_method,
                     name: _,
+                    route: _,
                     variables: _,
                     uses_context: _,
                 }) => http_method.clone().unwrap() == HttpType::HttpDelete,
@@ -998,11 +3500,16 @@ mod tests {
         if let BlockDetails::MethodBlock {
             name,
             http_method,
+            route,
             variables,
             uses_context,
         } = details
         {
             assert_eq!(name, "DeleteUserTask");
+            assert_eq!(
+                route.map(|route| route.path),
+                Some("/api/Test/userTask/{userTaskOid}".to_string())
+            );
             assert_eq!(uses_context, true);
             assert_eq!(variables.len(), 1);
             assert_eq!(http_method, Some(HttpType::HttpDelete));
@@ -1028,9 +3535,50 @@ mod tests {
         assert!(variables[0].details.is_some());
         let details = variables[0].details.clone().unwrap();
 
-        if let BlockDetails::VariableBlock { name, data_type } = details {
+        if let BlockDetails::VariableBlock {
+            name,
+            data_type,
+            assignments,
+        } = details
+        {
             assert_eq!(name, "user");
             assert_eq!(data_type, "User");
+            assert_eq!(
+                assignments
+                    .iter()
+                    .map(|assignment| assignment.property.as_str())
+                    .collect::<Vec<&str>>(),
+                vec![
+                    "UserOid",
+                    "UserName",
+                    "Email",
+                    "Password",
+                    "UserStatusId",
+                    "StartDate"
+                ]
+            );
+            assert_eq!(
+                assignments[0].references,
+                vec![Reference::Variable {
+                    name: "userOid".to_string(),
+                    path: vec![],
+                }]
+            );
+            assert_eq!(
+                assignments[1].references,
+                vec![Reference::Parameter {
+                    name: "adminName".to_string(),
+                    path: vec!["UserName".to_string()],
+                }]
+            );
+            assert_eq!(assignments[4].value, "100");
+            assert!(assignments[4].references.is_empty());
+            assert_eq!(
+                assignments[5].references,
+                vec![Reference::Member {
+                    name: "DateTime.UtcNow".to_string(),
+                }]
+            );
         } else {
             assert!(false);
         }
@@ -1041,9 +3589,27 @@ mod tests {
         assert!(variables[1].details.is_some());
         let details = variables[1].details.clone().unwrap();
 
-        if let BlockDetails::VariableBlock { name, data_type } = details {
+        if let BlockDetails::VariableBlock {
+            name,
+            data_type,
+            assignments,
+        } = details
+        {
             assert_eq!(name, "admin");
             assert_eq!(data_type, "Admin");
+            assert_eq!(
+                assignments[0].references,
+                vec![Reference::Call {
+                    name: "Guid.NewGuid".to_string(),
+                }]
+            );
+            assert_eq!(
+                assignments[1].references,
+                vec![Reference::Variable {
+                    name: "user".to_string(),
+                    path: vec!["UserOid".to_string()],
+                }]
+            );
         } else {
             assert!(false);
         }
@@ -1054,7 +3620,10 @@ mod tests {
         assert!(variables[2].details.is_some());
         let details = variables[2].details.clone().unwrap();
 
-        if let BlockDetails::VariableBlock { name, data_type } = details {
+        if let BlockDetails::VariableBlock {
+            name, data_type, ..
+        } = details
+        {
             assert_eq!(name, "userTask");
             assert_eq!(data_type, "UserTask");
         } else {
@@ -1084,18 +3653,66 @@ mod tests {
             return_data,
             syntax,
             has_return,
+            operators,
+            order_by,
+            skip,
+            take,
+            aggregate,
+            projection,
         } = details
         {
             assert_eq!(query_type, QueryType::Many);
-            assert_eq!(syntax, LinqSyntax::Query);
+            assert_eq!(syntax, LinqSyntax::Both);
             assert_eq!(has_return, true);
 
+            assert_eq!(
+                operators,
+                vec![
+                    LinqOperator {
+                        kind: OperatorKind::OrderBy,
+                        method: "OrderBy".to_string(),
+                        lambda_variable: Some("t".to_string()),
+                        argument: "t.OrderNumber".to_string(),
+                    },
+                    LinqOperator {
+                        kind: OperatorKind::Terminal,
+                        method: "ToList".to_string(),
+                        lambda_variable: None,
+                        argument: "".to_string(),
+                    },
+                ]
+            );
+
+            assert_eq!(
+                order_by,
+                vec![OrderKey {
+                    key: Expression::Column {
+                        shortcut: "ut".to_string(),
+                        path: vec!["OrderNumber".to_string()],
+                    },
+                    direction: SortDirection::Ascending,
+                }]
+            );
+            assert_eq!((skip, take, aggregate), (None, None, None));
+
             assert_eq!(tables.len(), 3);
 
             assert_eq!(tables[0].name, "TaskStatuses");
             assert_eq!(tables[0].shortcut, "uts");
             assert_eq!(tables[0].joined_tables, vec![]);
             assert_eq!(tables[0].return_frequency, 1);
+            assert_eq!(
+                tables[0].join,
+                Some(Join {
+                    kind: JoinKind::Inner,
+                    keys: vec![JoinKey {
+                        shortcut: "ut".to_string(),
+                        property: "TaskStatusId".to_string(),
+                        joined_property: "TaskStatusId".to_string(),
+                    }],
+                    group: None,
+                })
+            );
 
             assert_eq!(tables[1].name, "Users");
             assert_eq!(tables[1].shortcut, "u");
@@ -1104,8 +3721,12 @@ mod tests {
 
             assert_eq!(tables[2].name, "UserTasks");
             assert_eq!(tables[2].shortcut, "ut");
-            assert_eq!(tables[2].joined_tables, vec![]);
-            assert_eq!(tables[2].return_frequency, 6);
+            assert_eq!(
+                tables[2].joined_tables,
+                vec![tables[0].clone(), tables[1].clone()]
+            );
+            assert_eq!(tables[2].return_frequency, 5);
+            assert_eq!(tables[2].join, None);
 
             assert_eq!(where_clauses.len(), 1);
 
@@ -1123,29 +3744,53 @@ mod tests {
             assert_eq!(return_data[1].table, "UserTasks");
             assert_eq!(return_data[1].value, "Name");
 
-            assert_eq!(return_data[2].property, "CompleteDate");
+            assert_eq!(return_data[2].property, "TaskStatusId");
             assert_eq!(return_data[2].table, "UserTasks");
-            assert_eq!(return_data[2].value, "CompleteDate");
+            assert_eq!(return_data[2].value, "TaskStatusId");
 
-            assert_eq!(return_data[3].property, "TaskStatusId");
+            assert_eq!(return_data[3].property, "StartDate");
             assert_eq!(return_data[3].table, "UserTasks");
-            assert_eq!(return_data[3].value, "TaskStatusId");
+            assert_eq!(return_data[3].value, "StartDate");
 
-            assert_eq!(return_data[4].property, "StartDate");
+            assert_eq!(return_data[4].property, "OrderNumber");
             assert_eq!(return_data[4].table, "UserTasks");
-            assert_eq!(return_data[4].value, "StartDate");
-
-            assert_eq!(return_data[5].property, "OrderNumber");
-            assert_eq!(return_data[5].table, "UserTasks");
-            assert_eq!(return_data[5].value, "OrderNumber");
+            assert_eq!(return_data[4].value, "OrderNumber");
 
-            assert_eq!(return_data[6].property, "UserOid");
-            assert_eq!(return_data[6].table, "Users");
-            assert_eq!(return_data[6].value, "UserOid");
+            assert_eq!(return_data[5].property, "UserOid");
+            assert_eq!(return_data[5].table, "Users");
+            assert_eq!(return_data[5].value, "UserOid");
+
+            assert_eq!(return_data[6].property, "CompleteDate");
+            assert_eq!(return_data[6].table, "");
+            assert_eq!(return_data[6].value, "ut.CompleteDate.HasValue ? ut.CompleteDate.Value.ToString(\"yyyy-MM-dd HH:mm:ss\") : \"\"");
 
             assert_eq!(return_data[7].property, "TaskStatus");
             assert_eq!(return_data[7].table, "TaskStatuses");
             assert_eq!(return_data[7].value, "Name");
+
+            let Some(Projection::Object { type_name, fields }) = projection else {
+                panic!("Unexpected projection");
+            };
+            assert_eq!(type_name, Some("UserTaskDetails".to_string()));
+            assert_eq!(fields.len(), 8);
+            assert_eq!(
+                fields[0],
+                ProjectedField {
+                    name: "UserOid".to_string(),
+                    value: Projection::Column {
+                        shortcut: "u".to_string(),
+                        path: vec!["UserOid".to_string()],
+                    },
+                }
+            );
+            assert_eq!(
+                fields[3].value,
+                Projection::Computed {
+                    expression: "ut.CompleteDate.HasValue ? ut.CompleteDate.Value.ToString(\"yyyy-MM-dd HH:mm:ss\") : \"\"".to_string(),
+                    shortcuts: vec!["ut".to_string(), "ut".to_string()],
+                    properties: vec!["CompleteDate".to_string(), "CompleteDate".to_string()],
+                }
+            );
         } else {
             assert!(false);
         }
@@ -1164,11 +3809,14 @@ mod tests {
             return_data,
             syntax,
             has_return,
+            operators,
+            ..
         } = details
         {
             assert_eq!(query_type, QueryType::Unique);
             assert_eq!(syntax, LinqSyntax::Query);
             assert_eq!(has_return, true);
+            assert_eq!(operators.len(), 1);
 
             assert_eq!(tables.len(), 2);
 
@@ -1179,8 +3827,8 @@ mod tests {
 
             assert_eq!(tables[1].name, "UserTasks");
             assert_eq!(tables[1].shortcut, "ut");
-            assert_eq!(tables[1].joined_tables, vec![]);
-            assert_eq!(tables[1].return_frequency, 7);
+            assert_eq!(tables[1].joined_tables, vec![tables[0].clone()]);
+            assert_eq!(tables[1].return_frequency, 6);
 
             assert_eq!(where_clauses.len(), 1);
 
@@ -1205,21 +3853,21 @@ mod tests {
             assert_eq!(return_data[2].table, "UserTasks");
             assert_eq!(return_data[2].value, "Name");
 
-            assert_eq!(return_data[3].property, "CompleteDate");
+            assert_eq!(return_data[3].property, "TaskStatusId");
             assert_eq!(return_data[3].table, "UserTasks");
-            assert_eq!(return_data[3].value, "CompleteDate");
+            assert_eq!(return_data[3].value, "TaskStatusId");
 
-            assert_eq!(return_data[4].property, "TaskStatusId");
+            assert_eq!(return_data[4].property, "StartDate");
             assert_eq!(return_data[4].table, "UserTasks");
-            assert_eq!(return_data[4].value, "TaskStatusId");
+            assert_eq!(return_data[4].value, "StartDate");
 
-            assert_eq!(return_data[5].property, "StartDate");
+            assert_eq!(return_data[5].property, "OrderNumber");
             assert_eq!(return_data[5].table, "UserTasks");
-            assert_eq!(return_data[5].value, "StartDate");
+            assert_eq!(return_data[5].value, "OrderNumber");
 
-            assert_eq!(return_data[6].property, "OrderNumber");
-            assert_eq!(return_data[6].table, "UserTasks");
-            assert_eq!(return_data[6].value, "OrderNumber");
+            assert_eq!(return_data[6].property, "CompleteDate");
+            assert_eq!(return_data[6].table, "");
+            assert_eq!(return_data[6].value, "ut.CompleteDate.HasValue ? ut.CompleteDate.Value.ToString(\"yyyy-MM-dd HH:mm:ss\") : \"\"");
 
             assert_eq!(return_data[7].property, "TaskStatus");
             assert_eq!(return_data[7].table, "TaskStatuses");
@@ -1242,11 +3890,20 @@ mod tests {
             return_data,
             syntax,
             has_return,
+            operators,
+            ..
         } = details
         {
             assert_eq!(query_type, QueryType::Unique);
             assert_eq!(syntax, LinqSyntax::Lambda);
             assert_eq!(has_return, false);
+            assert_eq!(
+                operators
+                    .iter()
+                    .map(|operator| operator.kind.clone())
+                    .collect::<Vec<OperatorKind>>(),
+                vec![OperatorKind::Where, OperatorKind::Terminal]
+            );
 
             assert_eq!(tables.len(), 1);
 
@@ -1281,11 +3938,20 @@ mod tests {
             return_data,
             syntax,
             has_return,
+            operators,
+            ..
         } = details
         {
             assert_eq!(query_type, QueryType::Unique);
             assert_eq!(syntax, LinqSyntax::Lambda);
             assert_eq!(has_return, false);
+            assert_eq!(
+                operators
+                    .iter()
+                    .map(|operator| operator.kind.clone())
+                    .collect::<Vec<OperatorKind>>(),
+                vec![OperatorKind::Where, OperatorKind::Terminal]
+            );
 
             assert_eq!(tables.len(), 1);
 
@@ -1320,11 +3986,20 @@ mod tests {
             return_data,
             syntax,
             has_return,
+            operators,
+            ..
         } = details
         {
             assert_eq!(query_type, QueryType::Unique);
             assert_eq!(syntax, LinqSyntax::Lambda);
             assert_eq!(has_return, false);
+            assert_eq!(
+                operators
+                    .iter()
+                    .map(|operator| operator.kind.clone())
+                    .collect::<Vec<OperatorKind>>(),
+                vec![OperatorKind::Where, OperatorKind::Terminal]
+            );
 
             assert_eq!(tables.len(), 1);
 
@@ -1443,6 +4118,13 @@ mod tests {
             lines: lines.iter().map(|x| x.to_string()).collect::<Vec<String>>(),
             class_name: None,
             blocks: None,
+            route_prefix: None,
+            class_attributes: vec![],
+            tokens: vec![],
+            line_numbers: vec![],
+            diagnostics: vec![],
+            models: vec![],
+            classes: vec![],
         };
 
         data = analyze_lines(data);
@@ -1450,6 +4132,979 @@ mod tests {
         assert!(data.class_name.is_some());
         assert_eq!(data.class_name.unwrap(), "Test2Controller");
         assert!(data.blocks.is_some());
-        assert_eq!(data.blocks.unwrap().len(), 38);
+        // includes the single line `new FileResult() { ... }` and
+        // `new string[] { ... }` initializers
+        assert_eq!(data.blocks.unwrap().len(), 40);
+    }
+
+    #[test]
+    fn analyze_write_blocks() {
+        let data = analyze_file("./tests/mocks/input.cs");
+
+        let writes = data
+            .blocks
+            .unwrap()
+            .into_iter()
+            .filter(|block| block.block_type == BlockType::Write)
+            .collect::<Vec<Block>>();
+
+        let spans = writes
+            .iter()
+            .map(|block| (block.start, block.end.unwrap()))
+            .collect::<Vec<(u32, u32)>>();
+
+        assert_eq!(
+            spans,
+            vec![
+                (42, 42),
+                (50, 50),
+                (52, 52),
+                (110, 111),
+                (112, 112),
+                (147, 147),
+                (148, 148),
+                (160, 161),
+                (162, 162),
+                (180, 180),
+                (181, 181),
+            ]
+        );
+
+        if let Some(BlockDetails::InsertBlock {
+            table,
+            variable,
+            assignments,
+            many,
+        }) = &writes[0].details
+        {
+            assert_eq!(table, "Users");
+            assert_eq!(variable, "user");
+            assert_eq!(assignments.len(), 6);
+            assert_eq!(
+                assignments[5],
+                Assignment {
+                    property: "StartDate".to_string(),
+                    value: "DateTime.UtcNow".to_string(),
+                    references: vec![Reference::Member {
+                        name: "DateTime.UtcNow".to_string(),
+                    }],
+                }
+            );
+            assert!(!many);
+        } else {
+            panic!("Unexpected block details");
+        }
+
+        if let Some(BlockDetails::UpdateBlock {
+            table,
+            variable,
+            where_clauses,
+            assignments,
+        }) = &writes[3].details
+        {
+            assert_eq!(table, "UserTasks");
+            assert_eq!(variable, "userTask");
+            assert_eq!(where_clauses[0].value, "t.UserTaskOid == userTaskOid");
+            assert_eq!(
+                assignments
+                    .iter()
+                    .map(|assignment| assignment.property.as_str())
+                    .collect::<Vec<&str>>(),
+                vec!["CompleteDate", "TaskStatusId"]
+            );
+        } else {
+            panic!("Unexpected block details");
+        }
+
+        if let Some(BlockDetails::DeleteBlock {
+            table,
+            variable,
+            where_clauses,
+            ..
+        }) = &writes[5].details
+        {
+            assert_eq!(table, "UserTasks");
+            assert_eq!(variable, "userTask");
+            assert_eq!(where_clauses.len(), 1);
+        } else {
+            panic!("Unexpected block details");
+        }
+
+        assert_eq!(
+            writes[6].details,
+            Some(BlockDetails::SaveChangesBlock {
+                variable: "cx".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn analyze_where_expressions() {
+        let data = analyze_file("./tests/mocks/input4.cs");
+
+        let expressions = data
+            .blocks
+            .unwrap()
+            .into_iter()
+            .filter_map(|block| match block.details {
+                Some(BlockDetails::SelectBlock { where_clauses, .. }) => {
+                    Some(where_clauses[0].expression.clone().unwrap())
+                }
+                _ => None,
+            })
+            .collect::<Vec<Expression>>();
+
+        let column = |name: &str| Expression::Column {
+            shortcut: "t".to_string(),
+            path: vec![name.to_string()],
+        };
+
+        assert_eq!(
+            expressions[2],
+            Expression::Binary {
+                operator: "&&".to_string(),
+                left: Box::new(Expression::Binary {
+                    operator: "&&".to_string(),
+                    left: Box::new(Expression::Call {
+                        target: Box::new(Expression::Parameter {
+                            name: "userOids".to_string(),
+                            path: vec![],
+                        }),
+                        method: "Contains".to_string(),
+                        arguments: vec![column("UserOid")],
+                    }),
+                    right: Box::new(Expression::Not {
+                        operand: Box::new(column("IsDeleted")),
+                    }),
+                }),
+                right: Box::new(Expression::Binary {
+                    operator: "!=".to_string(),
+                    left: Box::new(Expression::Literal {
+                        value: "3".to_string(),
+                    }),
+                    right: Box::new(column("TaskStatusId")),
+                }),
+            }
+        );
+
+        if let Expression::Binary { left, right, .. } = &expressions[0] {
+            assert_eq!(
+                left.as_ref(),
+                &Expression::Binary {
+                    operator: "==".to_string(),
+                    left: Box::new(Expression::Column {
+                        shortcut: "ut".to_string(),
+                        path: vec!["UserOid".to_string()],
+                    }),
+                    right: Box::new(Expression::Parameter {
+                        name: "userOid".to_string(),
+                        path: vec![],
+                    }),
+                }
+            );
+            assert!(matches!(
+                right.as_ref(),
+                Expression::Binary { operator, .. } if operator == "||"
+            ));
+        } else {
+            panic!("Unexpected expression");
+        }
+
+        assert_eq!(
+            expressions[4],
+            Expression::Raw {
+                text: "where IsFlagged(t)".to_string()
+            }
+        );
+        assert!(data.diagnostics.iter().any(|diagnostic| {
+            diagnostic.code == DiagnosticCode::UnreadableQuery && diagnostic.line == Some(68)
+        }));
+    }
+
+    #[test]
+    fn analyze_join_tables() {
+        let data = analyze_file("./tests/mocks/input5.cs");
+
+        let tables = data
+            .blocks
+            .unwrap()
+            .into_iter()
+            .find_map(|block| match block.details {
+                Some(BlockDetails::SelectBlock { tables, .. }) => Some(tables),
+                _ => None,
+            })
+            .unwrap();
+
+        let shortcuts = tables
+            .iter()
+            .map(|table| table.shortcut.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(shortcuts, vec!["s", "c", "u", "ut"]);
+
+        let join = |table: usize| tables[table].join.clone().unwrap();
+
+        assert_eq!(join(0).kind, JoinKind::Left);
+        assert_eq!(join(0).group, Some("statuses".to_string()));
+        assert_eq!(
+            join(0).keys,
+            vec![JoinKey {
+                shortcut: "ut".to_string(),
+                property: "TaskStatusId".to_string(),
+                joined_property: "TaskStatusId".to_string(),
+            }]
+        );
+        assert_eq!(join(1).kind, JoinKind::Inner);
+        assert_eq!(join(1).keys[0].shortcut, "u");
+        assert_eq!(join(1).keys[0].joined_property, "CompanyOid");
+
+        let names = |tables: &[crate::Table]| {
+            tables
+                .iter()
+                .map(|table| table.name.clone())
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(tables[3].join, None);
+        assert_eq!(
+            names(&tables[3].joined_tables),
+            vec!["TaskStatuses", "Users"]
+        );
+        assert_eq!(
+            names(&tables[3].joined_tables[1].joined_tables),
+            vec!["Companies"]
+        );
+        assert_eq!(tables[0].joined_tables, vec![]);
+    }
+
+    #[test]
+    fn analyze_operator_chains() {
+        let data = analyze_file("./tests/mocks/input5.cs");
+
+        let selects = data
+            .blocks
+            .unwrap()
+            .into_iter()
+            .filter_map(|block| match block.details {
+                Some(BlockDetails::SelectBlock {
+                    where_clauses,
+                    operators,
+                    syntax,
+                    ..
+                }) => Some((block.start, block.end, where_clauses, operators, syntax)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let (start, end, where_clauses, operators, syntax) = &selects[2];
+
+        assert_eq!((*start, *end), (45, Some(58)));
+        assert_eq!(*syntax, LinqSyntax::Lambda);
+        assert_eq!(
+            operators
+                .iter()
+                .map(|operator| (operator.kind.clone(), operator.argument.as_str()))
+                .collect::<Vec<(OperatorKind, &str)>>()[..7],
+            [
+                (OperatorKind::Include, "t.User"),
+                (OperatorKind::Where, "t.UserOid == userOid"),
+                (OperatorKind::Where, "t.CompleteDate == null"),
+                (OperatorKind::OrderByDescending, "t.DueDate"),
+                (OperatorKind::ThenBy, "t.Name"),
+                (OperatorKind::Skip, "page * 20"),
+                (OperatorKind::Take, "20"),
+            ]
+        );
+        assert_eq!(operators[7].kind, OperatorKind::Select);
+        assert!(operators[7].argument.starts_with("new TaskSummary\n"));
+        assert_eq!(operators[8].method, "ToList");
+        assert_eq!(where_clauses.len(), 2);
+
+        let (_, _, where_clauses, operators, _) = &selects[3];
+
+        assert_eq!(operators[0].kind, OperatorKind::Count);
+        assert_eq!(
+            where_clauses[0].value,
+            "t.UserOid == userOid && t.DueDate < DateTime.UtcNow"
+        );
+    }
+
+    #[test]
+    fn analyze_ordering_and_aggregates() {
+        let data = analyze_file("./tests/mocks/input5.cs");
+
+        let selects = data
+            .blocks
+            .unwrap()
+            .into_iter()
+            .filter_map(|block| block.details)
+            .filter(|details| matches!(details, BlockDetails::SelectBlock { .. }))
+            .collect::<Vec<BlockDetails>>();
+
+        let column = |shortcut: &str, name: &str| Expression::Column {
+            shortcut: shortcut.to_string(),
+            path: vec![name.to_string()],
+        };
+
+        if let BlockDetails::SelectBlock {
+            query_type,
+            order_by,
+            ..
+        } = &selects[1]
+        {
+            assert_eq!(*query_type, QueryType::FirstOrThrow);
+            assert_eq!(
+                *order_by,
+                vec![
+                    OrderKey {
+                        key: column("ut", "DueDate"),
+                        direction: SortDirection::Descending,
+                    },
+                    OrderKey {
+                        key: column("ut", "Name"),
+                        direction: SortDirection::Ascending,
+                    },
+                ]
+            );
+        } else {
+            panic!("Unexpected block details");
+        }
+
+        if let BlockDetails::SelectBlock {
+            query_type,
+            order_by,
+            skip,
+            take,
+            aggregate,
+            ..
+        } = &selects[2]
+        {
+            assert_eq!(*query_type, QueryType::Many);
+            assert_eq!(order_by[0].key, column("t", "DueDate"));
+            assert_eq!(order_by[0].direction, SortDirection::Descending);
+            assert_eq!(order_by[1].key, column("t", "Name"));
+            assert_eq!(*skip, Some("page * 20".to_string()));
+            assert_eq!(*take, Some("20".to_string()));
+            assert_eq!(*aggregate, None);
+        } else {
+            panic!("Unexpected block details");
+        }
+
+        let aggregates = selects[3..6]
+            .iter()
+            .map(|details| match details {
+                BlockDetails::SelectBlock { aggregate, .. } => aggregate.clone(),
+                _ => None,
+            })
+            .collect::<Vec<Option<Aggregate>>>();
+
+        assert_eq!(
+            aggregates,
+            vec![
+                Some(Aggregate {
+                    kind: AggregateKind::Count,
+                    selector: None,
+                }),
+                Some(Aggregate {
+                    kind: AggregateKind::Any,
+                    selector: None,
+                }),
+                Some(Aggregate {
+                    kind: AggregateKind::Sum,
+                    selector: Some(column("t", "Hours")),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn analyze_select_projections() {
+        let data = analyze_file("./tests/mocks/input5.cs");
+
+        let selects = data
+            .blocks
+            .unwrap()
+            .into_iter()
+            .filter_map(|block| block.details)
+            .filter(|details| matches!(details, BlockDetails::SelectBlock { .. }))
+            .collect::<Vec<BlockDetails>>();
+
+        let column = |shortcut: &str, path: &[&str]| Projection::Column {
+            shortcut: shortcut.to_string(),
+            path: path.iter().map(|name| name.to_string()).collect(),
+        };
+        let field = |name: &str, value: Projection| ProjectedField {
+            name: name.to_string(),
+            value,
+        };
+
+        if let BlockDetails::SelectBlock {
+            projection,
+            return_data,
+            ..
+        } = &selects[6]
+        {
+            let Some(Projection::Object { type_name, fields }) = projection else {
+                panic!("Unexpected projection");
+            };
+
+            assert_eq!(*type_name, Some("TaskSummary".to_string()));
+            assert_eq!(
+                fields[0],
+                field("UserTaskOid", column("t", &["UserTaskOid"]))
+            );
+            assert_eq!(
+                fields[1],
+                field(
+                    "Status",
+                    Projection::Object {
+                        type_name: None,
+                        fields: vec![
+                            field("Name", column("t", &["TaskStatus", "Name"])),
+                            field("TaskStatusId", column("t", &["TaskStatusId"])),
+                        ],
+                    }
+                )
+            );
+            assert_eq!(fields[2], field("Owner", column("t", &["User", "Name"])));
+            assert_eq!(
+                fields[3],
+                field(
+                    "Hours",
+                    Projection::Computed {
+                        expression: "t.Hours ?? 0".to_string(),
+                        shortcuts: vec!["t".to_string()],
+                        properties: vec!["Hours".to_string()],
+                    }
+                )
+            );
+
+            let Projection::Collection {
+                shortcut,
+                path,
+                operators,
+                element,
+            } = &fields[4].value
+            else {
+                panic!("Unexpected projection");
+            };
+
+            assert_eq!(fields[4].name, "Comments");
+            assert_eq!(shortcut, "t");
+            assert_eq!(*path, vec!["Comments"]);
+            assert_eq!(
+                operators
+                    .iter()
+                    .map(|operator| operator.method.as_str())
+                    .collect::<Vec<&str>>(),
+                vec!["OrderBy", "Select", "ToList"]
+            );
+            assert_eq!(
+                **element.as_ref().unwrap(),
+                Projection::Object {
+                    type_name: Some("CommentDto".to_string()),
+                    fields: vec![field("Text", column("c", &["Text"]))],
+                }
+            );
+
+            assert_eq!(
+                return_data
+                    .iter()
+                    .map(|data| (data.property.as_str(), data.value.as_str()))
+                    .collect::<Vec<(&str, &str)>>(),
+                vec![
+                    ("UserTaskOid", "UserTaskOid"),
+                    ("Owner", "t.User.Name"),
+                    ("Hours", "t.Hours ?? 0"),
+                ]
+            );
+        } else {
+            panic!("Unexpected block details");
+        }
+    }
+
+    #[test]
+    fn analyze_classes_and_namespaces() {
+        let data = analyze_file("./tests/mocks/input6.cs");
+
+        assert_eq!(data.class_name, Some("TasksController".to_string()));
+        assert_eq!(data.route_prefix, Some("api/[controller]".to_string()));
+        assert!(data.diagnostics.is_empty());
+
+        let member = |name: &str, kind: MemberKind| Member {
+            name: name.to_string(),
+            kind,
+        };
+
+        assert_eq!(
+            data.classes
+                .iter()
+                .map(|class| (
+                    class.name.as_str(),
+                    class.namespace.as_deref(),
+                    class.parent.as_deref()
+                ))
+                .collect::<Vec<(&str, Option<&str>, Option<&str>)>>(),
+            vec![
+                ("TaskFilter", Some("Tasks.Api"), None),
+                ("Range", Some("Tasks.Api"), Some("TaskFilter")),
+                ("TasksController", Some("Tasks.Api"), None),
+                ("UsersController", Some("Tasks.Admin"), None),
+            ]
+        );
+
+        assert_eq!(
+            data.classes[0].members,
+            vec![
+                member("UserOid", MemberKind::Property),
+                member("Page", MemberKind::Property),
+                member("TaskFilter", MemberKind::Constructor),
+            ]
+        );
+
+        assert_eq!(
+            data.classes[2].base_types,
+            vec!["ControllerBase", "IDisposable"]
+        );
+        assert_eq!(
+            data.classes[2].attributes,
+            vec!["Route(\"api/[controller]\")", "ApiController"]
+        );
+        assert_eq!(
+            data.classes[2].members,
+            vec![
+                member("_dbFactory", MemberKind::Field),
+                member("TasksController", MemberKind::Constructor),
+                member("CountTasks", MemberKind::Method),
+                member("Dispose", MemberKind::Method),
+            ]
+        );
+
+        let blocks = data.blocks.unwrap();
+        let constructors = |start: u32, end: u32| {
+            blocks
+                .iter()
+                .filter(|block| block.start > start && block.end.is_some_and(|e| e < end))
+                .filter(|block| block.block_type == BlockType::Constructor)
+                .count()
+        };
+
+        // every class has its constructor, not only the controller
+        assert_eq!(
+            constructors(data.classes[0].start, data.classes[1].start),
+            1
+        );
+        assert_eq!(constructors(data.classes[3].start, u32::MAX), 1);
+
+        let routes = blocks
+            .iter()
+            .filter_map(|block| match &block.details {
+                Some(BlockDetails::MethodBlock {
+                    route: Some(route), ..
+                }) => Some(route.path.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<&str>>();
+
+        assert_eq!(
+            routes,
+            vec!["/api/Tasks/{userOid}", "/admin/Users/{userOid}"]
+        );
+    }
+
+    #[test]
+    fn analyze_records_and_primary_constructors() {
+        let data = analyze_file("./tests/mocks/input7.cs");
+
+        assert_eq!(data.class_name, Some("TestController".to_string()));
+        assert!(data.diagnostics.is_empty());
+
+        let member = |name: &str, kind: MemberKind| Member {
+            name: name.to_string(),
+            kind,
+        };
+
+        let controller = &data.classes[1];
+        assert_eq!(controller.namespace, Some("Test.Controllers".to_string()));
+        assert_eq!(controller.base_types, vec!["ControllerBase"]);
+        assert_eq!(
+            controller.members,
+            vec![
+                member("TestController", MemberKind::Constructor),
+                member("GetUsers", MemberKind::Method),
+            ]
+        );
+
+        let record = &data.classes[0];
+        assert_eq!(record.name, "TaskDto");
+        assert_eq!(record.namespace, Some("Test.Controllers".to_string()));
+        assert_eq!(
+            record.members,
+            vec![
+                member("UserTaskOid", MemberKind::Property),
+                member("Name", MemberKind::Property),
+                member("Tags", MemberKind::Property),
+            ]
+        );
+
+        let models = data
+            .models
+            .iter()
+            .map(|model| {
+                (
+                    model.name.as_str(),
+                    model
+                        .properties
+                        .iter()
+                        .map(|property| format!("{} {}", property.property_type, property.name))
+                        .collect::<Vec<String>>(),
+                )
+            })
+            .collect::<Vec<(&str, Vec<String>)>>();
+
+        assert_eq!(
+            models,
+            vec![
+                (
+                    "UserDto",
+                    vec!["Guid Id".to_string(), "string Name".to_string()]
+                ),
+                (
+                    "TaskKey",
+                    vec!["Guid UserOid".to_string(), "int OrderNumber".to_string()]
+                ),
+                (
+                    "TaskDto",
+                    vec![
+                        "Guid UserTaskOid".to_string(),
+                        "string Name".to_string(),
+                        "List<string> Tags".to_string(),
+                    ]
+                ),
+            ]
+        );
+
+        let blocks = data.blocks.unwrap();
+        assert_eq!(blocks[0].block_type, BlockType::Class);
+
+        let route = blocks.iter().find_map(|block| match &block.details {
+            Some(BlockDetails::MethodBlock { route, .. }) => route.clone(),
+            _ => None,
+        });
+        assert_eq!(route.unwrap().path, "/api/Test/{userOid}");
+    }
+
+    #[test]
+    fn analyze_tabs_strings_and_comments() {
+        let input = [
+            "[Route(\"api/[controller]\")]",
+            "public\tclass\tTabController",
+            "{",
+            "\t[HttpGet(\"items/{id}\")] // { not a block",
+            "\tpublic\tList<Item>\tGetItems(\tGuid id, [FromQuery] int page = 1)",
+            "\t{",
+            "\t\t/* } */ var message = \"{ \\\" }\";",
+            "\t\tif (Check(id) && message != $\"{id}\")",
+            "\t\t{",
+            "\t\t\treturn null;",
+            "\t\t}",
+            "\t}",
+            "}",
+        ];
+
+        let data = analyze_lines(Data {
+            lines: input.iter().map(|x| x.to_string()).collect::<Vec<String>>(),
+            class_name: None,
+            blocks: None,
+            route_prefix: None,
+            class_attributes: vec![],
+            tokens: vec![],
+            line_numbers: vec![],
+            diagnostics: vec![],
+            models: vec![],
+            classes: vec![],
+        });
+
+        assert_eq!(data.class_name, Some("TabController".to_string()));
+        assert_eq!(data.route_prefix, Some("api/[controller]".to_string()));
+
+        let blocks = data.blocks.unwrap();
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[1].block_type, BlockType::Method);
+        assert_eq!(blocks[2].block_type, BlockType::If);
+
+        if let Some(BlockDetails::MethodBlock {
+            name,
+            http_method,
+            route,
+            variables,
+            ..
+        }) = &blocks[1].details
+        {
+            assert_eq!(name, "GetItems");
+            assert_eq!(http_method, &Some(HttpType::HttpGet));
+            assert_eq!(
+                route.clone().map(|route| route.path),
+                Some("/api/Tab/items/{id}".to_string())
+            );
+            assert_eq!(variables.len(), 2);
+            assert_eq!(variables[0].name, "id");
+            assert_eq!(variables[0].variable_type, "Guid");
+            assert_eq!(variables[1].name, "page");
+            assert_eq!(variables[1].variable_type, "int");
+        } else {
+            panic!("Unexpected block details");
+        }
+
+        if let Some(BlockDetails::IfBlock { clause, is_else }) = &blocks[2].details {
+            assert_eq!(clause, "Check(id) && message != $\"{id}\"");
+            assert!(!is_else);
+        } else {
+            panic!("Unexpected block details");
+        }
+    }
+
+    #[test]
+    fn analyze_same_line_braces() {
+        let own_line = analyze_file("./tests/mocks/input.cs");
+        let same_line = analyze_file("./tests/mocks/input3.cs");
+
+        assert_eq!(same_line.class_name, own_line.class_name);
+        assert_eq!(same_line.class_attributes, own_line.class_attributes);
+
+        let summarize = |data: Data| {
+            data.blocks
+                .unwrap()
+                .into_iter()
+                .map(|block| (block.block_type, block.details))
+                .collect::<Vec<(BlockType, Option<BlockDetails>)>>()
+        };
+
+        assert_eq!(summarize(same_line), summarize(own_line));
+    }
+
+    /// Analyzes a few lines of source written in a test.
+    fn analyze_snippet(input: &[&str]) -> Data {
+        analyze_lines(Data {
+            lines: input.iter().map(|x| x.to_string()).collect::<Vec<String>>(),
+            class_name: None,
+            blocks: None,
+            route_prefix: None,
+            class_attributes: vec![],
+            tokens: vec![],
+            line_numbers: vec![],
+            diagnostics: vec![],
+            models: vec![],
+            classes: vec![],
+        })
+    }
+
+    #[test]
+    fn analyze_reports_diagnostics() {
+        let data = analyze_snippet(&[
+            "public class Plain",
+            "{",
+            "    [HttpHead(\"ping\")]",
+            "    public void Ping() { }",
+            "    }",
+            "}",
+            "[HttpPatch(\"items/{id}\")]",
+            "public void Patch(int id)",
+            "{",
+            "    if (id > 0) {",
+        ]);
+
+        assert_eq!(data.class_name, Some("Plain".to_string()));
+
+        let diagnostics = data
+            .diagnostics
+            .iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.code.clone(),
+                    diagnostic.severity.clone(),
+                    diagnostic.line,
+                )
+            })
+            .collect::<Vec<(DiagnosticCode, Severity, Option<u32>)>>();
+
+        assert_eq!(
+            diagnostics,
+            vec![
+                (DiagnosticCode::UnbalancedBraces, Severity::Error, Some(6)),
+                (DiagnosticCode::UnbalancedBraces, Severity::Error, Some(9)),
+                (DiagnosticCode::UnbalancedBraces, Severity::Error, Some(10)),
+                (
+                    DiagnosticCode::UnsupportedHttpMethod,
+                    Severity::Warning,
+                    Some(3)
+                ),
+            ]
+        );
+
+        let methods = data
+            .blocks
+            .unwrap()
+            .into_iter()
+            .filter_map(|block| match block.details {
+                Some(BlockDetails::MethodBlock {
+                    name,
+                    http_method,
+                    route,
+                    ..
+                }) => Some((name, http_method, route.map(|route| route.path))),
+                _ => None,
+            })
+            .collect::<Vec<(String, Option<HttpType>, Option<String>)>>();
+
+        assert_eq!(
+            methods,
+            vec![
+                ("Ping".to_string(), None, None),
+                (
+                    "Patch".to_string(),
+                    Some(HttpType::HttpPatch),
+                    Some("/items/{id}".to_string())
+                ),
+            ]
+        );
+
+        let data = analyze_snippet(&["{", "var x = 1;", "}"]);
+        assert_eq!(data.diagnostics.len(), 1);
+        assert_eq!(data.diagnostics[0].code, DiagnosticCode::MissingClass);
+        assert_eq!(data.diagnostics[0].line, None);
+    }
+
+    #[test]
+    fn analyze_queries_without_braces() {
+        let data = analyze_snippet(&[
+            "public class ItemsController : ControllerBase",
+            "{",
+            "    [HttpGet]",
+            "    public List<Item> Cheap(int min)",
+            "    {",
+            "        using (var cx = _dbFactory.CreateContext())",
+            "        {",
+            "            return (from i in cx.Items where i.Price > min select i).ToList();",
+            "        }",
+            "    }",
+            "    [HttpGet]",
+            "    public List<Item> Sorted(int min)",
+            "    {",
+            "        using (var cx = _dbFactory.CreateContext())",
+            "        {",
+            "            var items = from i in cx.Items",
+            "                        where i.Price > min",
+            "                        orderby i.Name descending",
+            "                        select i;",
+            "            return items;",
+            "        }",
+            "    }",
+            "}",
+        ]);
+
+        let selects = data
+            .blocks
+            .unwrap()
+            .into_iter()
+            .filter_map(|block| block.details)
+            .filter(|details| matches!(details, BlockDetails::SelectBlock { .. }))
+            .collect::<Vec<BlockDetails>>();
+
+        assert_eq!(selects.len(), 2);
+
+        for (details, returns) in selects.iter().zip([true, false]) {
+            if let BlockDetails::SelectBlock {
+                syntax,
+                tables,
+                where_clauses,
+                projection,
+                has_return,
+                ..
+            } = details
+            {
+                assert_eq!(*syntax, LinqSyntax::Query);
+                assert_eq!(tables.len(), 1);
+                assert_eq!(tables[0].name, "Items");
+                assert_eq!(tables[0].shortcut, "i");
+                assert_eq!(where_clauses.len(), 1);
+                assert_eq!(
+                    where_clauses[0].expression,
+                    Some(Expression::Binary {
+                        operator: ">".to_string(),
+                        left: Box::new(Expression::Column {
+                            shortcut: "i".to_string(),
+                            path: vec!["Price".to_string()],
+                        }),
+                        right: Box::new(Expression::Parameter {
+                            name: "min".to_string(),
+                            path: vec![],
+                        }),
+                    })
+                );
+                assert_eq!(
+                    *projection,
+                    Some(Projection::Row {
+                        shortcut: "i".to_string()
+                    })
+                );
+                assert_eq!(*has_return, returns);
+            }
+        }
+
+        if let BlockDetails::SelectBlock { order_by, .. } = &selects[1] {
+            assert_eq!(
+                *order_by,
+                vec![OrderKey {
+                    key: Expression::Column {
+                        shortcut: "i".to_string(),
+                        path: vec!["Name".to_string()],
+                    },
+                    direction: SortDirection::Descending,
+                }]
+            );
+        }
+    }
+
+    #[test]
+    fn analyze_multi_line_parameters() {
+        let data = analyze_snippet(&[
+            "public class ItemsController : ControllerBase",
+            "{",
+            "    [HttpPost]",
+            "    public async Task<IActionResult> Create(",
+            "        [FromBody] ItemDto dto,",
+            "        CancellationToken ct)",
+            "    {",
+            "        return Ok(dto);",
+            "    }",
+            "}",
+        ]);
+
+        let method = data
+            .blocks
+            .unwrap()
+            .into_iter()
+            .find(|block| block.block_type == BlockType::Method)
+            .and_then(|block| block.details);
+
+        if let Some(BlockDetails::MethodBlock {
+            name,
+            http_method,
+            variables,
+            ..
+        }) = method
+        {
+            assert_eq!(name, "Create");
+            assert_eq!(http_method, Some(HttpType::HttpPost));
+            assert_eq!(
+                variables
+                    .iter()
+                    .map(|variable| (variable.name.as_str(), variable.variable_type.as_str()))
+                    .collect::<Vec<_>>(),
+                vec![("dto", "ItemDto"), ("ct", "CancellationToken")]
+            );
+        } else {
+            panic!("Expected a method block");
+        }
     }
 }
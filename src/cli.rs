@@ -0,0 +1,576 @@
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use controller_to_router_rs::{
+    analyze_source,
+    diagnostics::format_diagnostic,
+    generate::{generate_index, generate_models, get_target_from_string, router_names, Target},
+    project::{find_controllers, find_sources, is_project_file},
+    types::read_type_map,
+    Analysis, Model, Severity, TypeMap,
+};
+
+#[derive(Parser)]
+#[command(
+    name = "controller_to_router_rs",
+    version,
+    about = "Converts ASP.NET controllers into TypeScript routers",
+    after_help = "Values missing from the command line are read from FILE_INPUT_NAME, \
+                  FILE_OUTPUT, FILE_OUTPUT_NAME, OUTPUT_TARGET and TYPE_MAP in .env"
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Prints the model the analyzer reads from a controller
+    Analyze {
+        /// The controller to analyze, defaults to ./input/$FILE_INPUT_NAME
+        file: Option<PathBuf>,
+        /// How to print the model, json and yaml follow a versioned schema
+        #[arg(short, long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+    /// Converts a controller into a router, or every controller in a directory
+    /// or .csproj into one router each plus an index that merges them
+    Convert {
+        /// A controller file, a directory or a .csproj, defaults to
+        /// ./input/$FILE_INPUT_NAME
+        input: Option<PathBuf>,
+        /// The file or directory to write to
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// The router to generate: trpc, express or module
+        #[arg(short, long)]
+        target: Option<String>,
+        /// A directory of DTO and entity classes to write interfaces and
+        /// schemas for, next to the ones found in the controllers
+        #[arg(short, long)]
+        models: Option<PathBuf>,
+        /// A YAML file mapping custom C# types to TypeScript, e.g.
+        /// `Money: string`
+        #[arg(long)]
+        types: Option<PathBuf>,
+        /// Print the router instead of writing it
+        #[arg(long, conflicts_with = "check")]
+        stdout: bool,
+        /// Exit with an error when a written router is out of date or the
+        /// analysis reports errors, nothing is written
+        #[arg(long)]
+        check: bool,
+    },
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum Format {
+    Text,
+    Json,
+    Yaml,
+}
+
+/// The values `.env` provides for anything missing from the command line.
+pub struct Defaults {
+    pub input: Option<PathBuf>,
+    pub output: Option<PathBuf>,
+    pub file_output: bool,
+    pub target: Option<String>,
+    pub types: Option<PathBuf>,
+}
+
+pub fn read_defaults() -> Defaults {
+    Defaults {
+        input: std::env::var("FILE_INPUT_NAME")
+            .ok()
+            .map(|name| Path::new("./input").join(name)),
+        output: std::env::var("FILE_OUTPUT_NAME")
+            .ok()
+            .map(|name| Path::new("./output").join(name)),
+        file_output: std::env::var("FILE_OUTPUT").unwrap_or_default() == "true",
+        target: std::env::var("OUTPUT_TARGET").ok(),
+        types: std::env::var("TYPE_MAP").ok().map(PathBuf::from),
+    }
+}
+
+pub fn run(cli: Cli, defaults: Defaults) -> Result<ExitCode, String> {
+    let command = cli.command.unwrap_or(Command::Convert {
+        input: None,
+        output: None,
+        target: None,
+        models: None,
+        types: None,
+        stdout: false,
+        check: false,
+    });
+
+    match command {
+        Command::Analyze { file, format } => {
+            let file = resolve_input(file, &defaults)?;
+            let analysis = read_analysis(&file)?;
+
+            match format {
+                Format::Text => {
+                    println!("class: {}", analysis.class_name().unwrap_or("-"));
+                    println!(
+                        "route prefix: {}",
+                        analysis.data.route_prefix.as_deref().unwrap_or("-")
+                    );
+                    println!("attributes: {:?}", analysis.data.class_attributes);
+                    println!("{:#?}", analysis.blocks());
+                }
+                Format::Json => println!("{}", analysis.to_json()),
+                Format::Yaml => print!("{}", analysis.to_yaml()),
+            }
+
+            Ok(exit_code(!analysis.has_errors()))
+        }
+        Command::Convert {
+            input,
+            output,
+            target,
+            models,
+            types,
+            stdout,
+            check,
+        } => {
+            let input = resolve_input(input, &defaults)?;
+            let target = resolve_target(target, &defaults)?;
+            let types = read_types(types.or_else(|| defaults.types.clone()))?;
+            let batch = input.is_dir() || is_project_file(&input);
+
+            let stdout = stdout || (output.is_none() && !defaults.file_output && !check);
+            let output = match output {
+                Some(output) => Some(output),
+                None if batch => None,
+                None => defaults.output.clone(),
+            };
+
+            let files = find_controllers(&input)?;
+
+            if batch && files.is_empty() {
+                return Err(format!("no controllers found in {}", input.display()));
+            }
+
+            let mut success = true;
+            let mut routers = vec![];
+            let mut router_files: Vec<PathBuf> = vec![];
+            let mut summary = vec![];
+            let mut destination = output_path(batch, Path::new("index"), &output);
+            let mut models_found = match &models {
+                Some(directory) => read_models(directory)?,
+                None => vec![],
+            };
+
+            for file in &files {
+                let analysis = match read_analysis(file) {
+                    Ok(analysis) => analysis,
+                    Err(message) if batch => {
+                        eprintln!("error: {}", message);
+                        summary.push((file.clone(), Status::Failed, "unreadable".to_string()));
+                        success = false;
+                        continue;
+                    }
+                    Err(message) => return Err(message),
+                };
+
+                let router = analysis.generate_with_types(&target, &types);
+                let (status, notes) = conversion_status(&analysis, &router);
+                let module = module_name(file);
+                let names = match analysis.class_name() {
+                    Some(_) => router_names(&analysis.data),
+                    None => vec![],
+                };
+
+                // routers are written flat by file name and merged by router
+                // name, a second one with either name would overwrite the first
+                let clash = routers
+                    .iter()
+                    .position(|(other_module, other_name)| {
+                        *other_module == module || names.contains(other_name)
+                    })
+                    .filter(|_| batch && !names.is_empty());
+
+                if let Some(clash) = clash {
+                    let notes = format!("same name as {}", router_files[clash].display());
+                    summary.push((file.clone(), Status::Failed, notes));
+                    success = false;
+                    continue;
+                }
+
+                success = success && status != Status::Failed;
+                summary.push((file.clone(), status, notes));
+
+                for name in names {
+                    routers.push((module.clone(), name));
+                    router_files.push(file.clone());
+                }
+
+                models_found.extend(analysis.models().iter().cloned());
+
+                destination = output_path(batch, file, &output);
+                success = emit(&destination, &router, stdout, check)? && success;
+                success = success && !(check && analysis.has_errors());
+            }
+
+            if !models_found.is_empty() {
+                let mut seen = vec![];
+                models_found.retain(|model| {
+                    let first = !seen.contains(&model.name);
+                    seen.push(model.name.clone());
+                    first
+                });
+
+                let schemas = generate_models(&models_found, &types);
+                let destination = destination.with_file_name("schemas.ts");
+                success = emit(&destination, &schemas, stdout, check)? && success;
+            }
+
+            if batch {
+                let index = generate_index(&routers, &target);
+                let destination = output_path(batch, Path::new("index"), &output);
+                success = emit(&destination, &index, stdout, check)? && success;
+
+                print_summary(&summary);
+            }
+
+            Ok(exit_code(success))
+        }
+    }
+}
+
+fn exit_code(success: bool) -> ExitCode {
+    if success {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn resolve_input(input: Option<PathBuf>, defaults: &Defaults) -> Result<PathBuf, String> {
+    input
+        .or_else(|| defaults.input.clone())
+        .ok_or_else(|| "no input given, pass a file or set FILE_INPUT_NAME in .env".to_string())
+}
+
+fn resolve_target(target: Option<String>, defaults: &Defaults) -> Result<Target, String> {
+    let target = target
+        .or_else(|| defaults.target.clone())
+        .unwrap_or_else(|| "trpc".to_string());
+
+    get_target_from_string(&target).ok_or_else(|| {
+        format!(
+            "unknown target {}, use one of: trpc, express, module",
+            target
+        )
+    })
+}
+
+/// How much of a controller made it into its router.
+#[derive(Debug, PartialEq)]
+enum Status {
+    Converted,
+    PartiallyConverted,
+    Failed,
+}
+
+fn get_status_string(status: &Status) -> &'static str {
+    match status {
+        Status::Converted => "converted",
+        Status::PartiallyConverted => "partially converted",
+        Status::Failed => "failed",
+    }
+}
+
+/// A controller with errors or without a class failed, one with warnings or
+/// statements left as `// TODO` in the router was partially converted.
+fn conversion_status(analysis: &Analysis, router: &str) -> (Status, String) {
+    let count = |severity: Severity| {
+        analysis
+            .diagnostics()
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == severity)
+            .count()
+    };
+
+    let errors = count(Severity::Error);
+    let warnings = count(Severity::Warning);
+    let todos = router.matches("// TODO").count();
+
+    let status = if errors > 0 || analysis.class_name().is_none() {
+        Status::Failed
+    } else if warnings > 0 || todos > 0 {
+        Status::PartiallyConverted
+    } else {
+        Status::Converted
+    };
+
+    let notes = [(errors, "error"), (warnings, "warning"), (todos, "TODO")]
+        .iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, name)| format!("{} {}{}", count, name, if *count == 1 { "" } else { "s" }))
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    (status, notes)
+}
+
+/// The summary goes to stderr so `--stdout` output stays valid TypeScript.
+fn print_summary(summary: &[(PathBuf, Status, String)]) {
+    eprintln!();
+
+    for (file, status, notes) in summary {
+        if notes.is_empty() {
+            eprintln!("{:<21}{}", get_status_string(status), file.display());
+        } else {
+            eprintln!(
+                "{:<21}{} ({})",
+                get_status_string(status),
+                file.display(),
+                notes
+            );
+        }
+    }
+
+    let totals = [
+        Status::Converted,
+        Status::PartiallyConverted,
+        Status::Failed,
+    ]
+    .iter()
+    .map(|status| {
+        let count = summary
+            .iter()
+            .filter(|(_, other, _)| other == status)
+            .count();
+        format!("{} {}", count, get_status_string(status))
+    })
+    .collect::<Vec<String>>()
+    .join(", ");
+
+    eprintln!("{} controllers: {}", summary.len(), totals);
+}
+
+/// The module a router is written to, which the index imports it from.
+fn module_name(file: &Path) -> String {
+    file.file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Where the routers of `file` are written. A batch, or an output that is an
+/// existing directory, gets one `<Controller>.ts` per controller file and
+/// without an output everything goes to `./output`.
+fn output_path(batch: bool, file: &Path, output: &Option<PathBuf>) -> PathBuf {
+    let file_name = Path::new(&module_name(file)).with_extension("ts");
+
+    match output {
+        Some(output) if batch || output.is_dir() => output.join(file_name),
+        Some(output) => output.clone(),
+        None => Path::new("./output").join(file_name),
+    }
+}
+
+fn read_types(file: Option<PathBuf>) -> Result<TypeMap, String> {
+    let file = match file {
+        Some(file) => file,
+        None => return Ok(TypeMap::default()),
+    };
+
+    let source = std::fs::read_to_string(&file)
+        .map_err(|error| format!("could not read {}: {}", file.display(), error))?;
+
+    read_type_map(&source).map_err(|error| format!("{}: {}", file.display(), error))
+}
+
+/// The models declared in every `.cs` file below a directory.
+fn read_models(directory: &Path) -> Result<Vec<Model>, String> {
+    let mut models = vec![];
+
+    for file in find_sources(directory)? {
+        let source = std::fs::read_to_string(&file)
+            .map_err(|error| format!("could not read {}: {}", file.display(), error))?;
+
+        models.extend(analyze_source(&source).data.models);
+    }
+
+    Ok(models)
+}
+
+fn read_analysis(file: &Path) -> Result<Analysis, String> {
+    let source = std::fs::read_to_string(file)
+        .map_err(|error| format!("could not read {}: {}", file.display(), error))?;
+
+    let analysis = analyze_source(&source);
+
+    for diagnostic in analysis.diagnostics() {
+        eprintln!(
+            "{}",
+            format_diagnostic(&file.display().to_string(), diagnostic)
+        );
+    }
+
+    Ok(analysis)
+}
+
+/// Prints, checks or writes a generated file, returns false when `--check`
+/// finds it out of date.
+fn emit(destination: &Path, contents: &str, stdout: bool, check: bool) -> Result<bool, String> {
+    if stdout {
+        println!("{}", contents);
+    } else if check {
+        let current = std::fs::read_to_string(destination).unwrap_or_default();
+
+        if current != contents {
+            eprintln!("{} is out of date", destination.display());
+            return Ok(false);
+        }
+    } else {
+        write_router(destination, contents)?;
+    }
+
+    Ok(true)
+}
+
+fn write_router(destination: &Path, router: &str) -> Result<(), String> {
+    if let Some(parent) = destination.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|error| format!("could not create {}: {}", parent.display(), error))?;
+    }
+
+    std::fs::write(destination, router)
+        .map_err(|error| format!("could not write {}: {}", destination.display(), error))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use std::process::ExitCode;
+
+    use super::{
+        conversion_status, output_path, read_analysis, resolve_target, run, Cli, Command, Defaults,
+        Status,
+    };
+    use controller_to_router_rs::generate::Target;
+
+    fn defaults(target: Option<&str>) -> Defaults {
+        Defaults {
+            input: None,
+            output: None,
+            file_output: false,
+            target: target.map(|target| target.to_string()),
+            types: None,
+        }
+    }
+
+    #[test]
+    fn targets_fall_back_to_env_and_trpc() {
+        assert_eq!(
+            resolve_target(Some("express".to_string()), &defaults(Some("module"))),
+            Ok(Target::Express)
+        );
+        assert_eq!(
+            resolve_target(None, &defaults(Some("module"))),
+            Ok(Target::Module)
+        );
+        assert_eq!(resolve_target(None, &defaults(None)), Ok(Target::Trpc));
+        assert!(resolve_target(Some("koa".to_string()), &defaults(None)).is_err());
+    }
+
+    #[test]
+    fn output_paths() {
+        let file = Path::new("./tests/mocks/input.cs");
+
+        assert_eq!(
+            output_path(false, file, &Some(PathBuf::from("out/router.ts"))),
+            PathBuf::from("out/router.ts")
+        );
+        assert_eq!(
+            output_path(false, file, &Some(PathBuf::from("./tests"))),
+            PathBuf::from("./tests/input.ts")
+        );
+        assert_eq!(
+            output_path(true, file, &None),
+            PathBuf::from("./output/input.ts")
+        );
+        assert_eq!(
+            output_path(true, file, &Some(PathBuf::from("out/router.ts"))),
+            PathBuf::from("out/router.ts/input.ts")
+        );
+    }
+
+    #[test]
+    fn summarizes_conversions() {
+        let controllers = Path::new("./tests/mocks/project/Controllers");
+
+        let analysis = read_analysis(&controllers.join("OrdersController.cs")).unwrap();
+        let router = analysis.generate(&Target::Trpc);
+        assert_eq!(
+            conversion_status(&analysis, &router),
+            (Status::PartiallyConverted, "1 TODO".to_string())
+        );
+
+        let analysis = read_analysis(&controllers.join("Admin/UsersController.cs")).unwrap();
+        let router = analysis.generate(&Target::Trpc);
+        assert_eq!(
+            conversion_status(&analysis, &router),
+            (Status::Failed, "1 error, 1 TODO".to_string())
+        );
+    }
+
+    fn convert(input: &str, output: &Path) -> ExitCode {
+        let cli = Cli {
+            command: Some(Command::Convert {
+                input: Some(PathBuf::from(input)),
+                output: Some(output.to_path_buf()),
+                target: None,
+                models: None,
+                types: None,
+                stdout: false,
+                check: false,
+            }),
+        };
+
+        run(cli, defaults(None)).unwrap()
+    }
+
+    #[test]
+    fn fails_on_failed_and_clashing_controllers() {
+        let output = std::env::temp_dir().join("controller_to_router_rs_clash");
+        let _ = std::fs::remove_dir_all(&output);
+
+        assert_eq!(convert("./tests/mocks/clash", &output), ExitCode::FAILURE);
+
+        let index = std::fs::read_to_string(output.join("index.ts")).unwrap();
+        assert_eq!(index.matches("import { ordersRouter }").count(), 1);
+        assert_eq!(index.matches("orders: ordersRouter").count(), 1);
+        assert!(std::fs::read_to_string(output.join("OrdersController.ts"))
+            .unwrap()
+            .contains("deleteOrder"));
+
+        // Admin/UsersController.cs has an unbalanced brace
+        let output = std::env::temp_dir().join("controller_to_router_rs_failed");
+        assert_eq!(
+            convert("./tests/mocks/project/Controllers", &output),
+            ExitCode::FAILURE
+        );
+
+        let _ = std::fs::remove_dir_all(output);
+    }
+
+    #[test]
+    fn indexes_every_controller_of_a_file() {
+        let output = std::env::temp_dir().join("controller_to_router_rs_shop");
+        assert_eq!(convert("./tests/mocks/shop", &output), ExitCode::SUCCESS);
+
+        let index = std::fs::read_to_string(output.join("index.ts")).unwrap();
+        assert!(index.contains("import { usersRouter } from \"./ShopController\";"));
+        assert!(index.contains("import { ordersRouter } from \"./ShopController\";"));
+        assert!(index.contains("  users: usersRouter,\n  orders: ordersRouter,\n"));
+
+        let _ = std::fs::remove_dir_all(output);
+    }
+}
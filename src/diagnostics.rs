@@ -0,0 +1,68 @@
+use crate::{Diagnostic, DiagnosticCode, Severity};
+
+pub fn get_diagnostic_code_string(code: &DiagnosticCode) -> &'static str {
+    match code {
+        DiagnosticCode::UnbalancedBraces => "CTR001",
+        DiagnosticCode::MissingClass => "CTR002",
+        DiagnosticCode::UnsupportedHttpMethod => "CTR003",
+        DiagnosticCode::UnreadableMethod => "CTR004",
+        DiagnosticCode::UnreadableQuery => "CTR005",
+    }
+}
+
+pub fn get_severity_string(severity: &Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    }
+}
+
+/// Formats a diagnostic the way compilers do, e.g.
+/// `sample.cs:12: error[CTR001]: closing brace has no matching opening brace`.
+pub fn format_diagnostic(file_name: &str, diagnostic: &Diagnostic) -> String {
+    let location = match diagnostic.line {
+        Some(line) => format!("{}:{}", file_name, line),
+        None => file_name.to_string(),
+    };
+
+    format!(
+        "{}: {}[{}]: {}",
+        location,
+        get_severity_string(&diagnostic.severity),
+        get_diagnostic_code_string(&diagnostic.code),
+        diagnostic.message
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_diagnostic;
+    use crate::{Diagnostic, DiagnosticCode, Severity};
+
+    #[test]
+    fn formats_diagnostics() {
+        let diagnostic = Diagnostic {
+            code: DiagnosticCode::UnbalancedBraces,
+            severity: Severity::Error,
+            line: Some(12),
+            message: "closing brace has no matching opening brace".to_string(),
+        };
+
+        assert_eq!(
+            format_diagnostic("sample.cs", &diagnostic),
+            "sample.cs:12: error[CTR001]: closing brace has no matching opening brace"
+        );
+
+        let diagnostic = Diagnostic {
+            code: DiagnosticCode::MissingClass,
+            severity: Severity::Warning,
+            line: None,
+            message: "no class declaration found".to_string(),
+        };
+
+        assert_eq!(
+            format_diagnostic("sample.cs", &diagnostic),
+            "sample.cs: warning[CTR002]: no class declaration found"
+        );
+    }
+}
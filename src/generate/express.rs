@@ -0,0 +1,354 @@
+use super::zod::{input_schema, write_schema_import};
+use super::{
+    controller_blocks, controllers, function_name, http_method, input_variables, method_blocks,
+    router_name, write_function, write_header, write_helper_functions, write_index_imports,
+    write_prisma_import, write_type_import, INDENT,
+};
+use crate::types::ts_type;
+use crate::{route::parse_route, BlockDetails, Data, HttpType, Route, TypeMap, Variable};
+
+pub fn generate_router(data: &Data, types: &TypeMap) -> String {
+    let mut out = String::new();
+    let controllers = controllers(data);
+    let blocks = controller_blocks(&controllers);
+    let qualified = controllers.len() > 1;
+
+    write_header(&controllers, &mut out);
+    out.push_str("import { Router } from \"express\";\n");
+
+    let inputs = input_variables(&blocks);
+    if !inputs.is_empty() {
+        out.push_str("import { z } from \"zod\";\n");
+    }
+    write_schema_import(&inputs, types, &mut out);
+    write_type_import(&blocks, types, &mut out);
+    write_prisma_import(&blocks, &mut out);
+
+    for controller in &controllers {
+        out.push('\n');
+        write_router(controller, types, qualified, &mut out);
+    }
+
+    out
+}
+
+fn write_router(data: &Data, types: &TypeMap, qualified: bool, out: &mut String) {
+    let blocks = data.blocks.clone().unwrap_or_default();
+    let router = router_name(&data.class_name);
+
+    write_helper_functions(data, &blocks, types, qualified, out);
+
+    let methods = method_blocks(&blocks)
+        .into_iter()
+        .filter(|block| http_method(block).is_some())
+        .collect::<Vec<_>>();
+
+    for method in &methods {
+        write_function(data, &blocks, types, method, qualified, out);
+    }
+
+    out.push_str(&format!("export const {} = Router();\n", router));
+
+    for method in &methods {
+        if let Some(BlockDetails::MethodBlock {
+            name,
+            http_method: Some(http_method),
+            route,
+            variables,
+            ..
+        }) = &method.details
+        {
+            let verb = match http_method {
+                HttpType::HttpGet => "get",
+                HttpType::HttpPost => "post",
+                HttpType::HttpPut => "put",
+                HttpType::HttpPatch => "patch",
+                HttpType::HttpDelete => "delete",
+            };
+
+            let arguments = variables
+                .iter()
+                .map(|variable| format!("input.{}", variable.name))
+                .collect::<Vec<String>>()
+                .join(", ");
+
+            let path = match route {
+                Some(route) => express_path(route),
+                None => express_path(&parse_route(
+                    &data.route_prefix,
+                    &None,
+                    &data.class_name,
+                    name,
+                )),
+            };
+
+            out.push('\n');
+            out.push_str(&format!(
+                "{}.{}(\"{}\", async (req, res) => {{\n",
+                router, verb, path
+            ));
+
+            if !variables.is_empty() {
+                let values = variables
+                    .iter()
+                    .map(|variable| {
+                        format!(
+                            "{}: {}",
+                            variable.name,
+                            request_argument(variable, route, types)
+                        )
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ");
+
+                out.push_str(&format!(
+                    "{}const input = {}.parse({{ {} }});\n",
+                    INDENT,
+                    input_schema(variables, types),
+                    values
+                ));
+            }
+
+            out.push_str(&format!(
+                "{}res.json(await {}({}));\n",
+                INDENT,
+                function_name(data, name, qualified),
+                arguments
+            ));
+            out.push_str("});\n");
+        }
+    }
+}
+
+pub fn generate_index(routers: &[(String, String)]) -> String {
+    let mut out = String::new();
+
+    out.push_str("import { Router } from \"express\";\n");
+    write_index_imports(routers, &mut out);
+    out.push_str("\nexport const appRouter = Router();\n");
+
+    if !routers.is_empty() {
+        out.push('\n');
+    }
+
+    for (_, router) in routers {
+        out.push_str(&format!("appRouter.use({});\n", router));
+    }
+
+    out
+}
+
+/// Converts a resolved ASP.NET route to an express path,
+/// `/api/Test/items/{id:int}/{page?}` becomes `/api/Test/items/:id/:page?`.
+pub fn express_path(route: &Route) -> String {
+    let mut path = String::new();
+    let mut parameters = route.parameters.iter();
+    let mut depth = 0;
+
+    for char in route.path.chars() {
+        match char {
+            '{' => {
+                depth += 1;
+
+                if depth > 1 {
+                    continue;
+                }
+
+                if let Some(parameter) = parameters.next() {
+                    let prefix = if parameter.catch_all { "*" } else { ":" };
+                    path.push_str(&format!("{}{}", prefix, parameter.name));
+
+                    if parameter.optional || parameter.default_value.is_some() {
+                        path.push('?');
+                    }
+                }
+            }
+            '}' => depth -= 1,
+            _ if depth == 0 => path.push(char),
+            _ => (),
+        }
+    }
+
+    path
+}
+
+/// ASP.NET binds route values first, simple types from the query string and
+/// complex types from the request body. A nullable type is bound like the
+/// type it wraps, and a value that is not sent stays missing.
+fn request_argument(variable: &Variable, route: &Option<Route>, types: &TypeMap) -> String {
+    let ts_type = ts_type(types, &variable.variable_type);
+    let (value_type, missing) = match ts_type.strip_suffix(" | null") {
+        Some(value_type) if variable.default_value.is_none() => (value_type, "null"),
+        Some(value_type) => (value_type, "undefined"),
+        None => (ts_type.as_str(), "undefined"),
+    };
+
+    let parameter = route.as_ref().and_then(|route| {
+        route
+            .parameters
+            .iter()
+            .find(|parameter| parameter.name.eq_ignore_ascii_case(&variable.name))
+    });
+
+    let (value, required) = match parameter {
+        Some(parameter) => (
+            format!("req.params.{}", parameter.name),
+            !parameter.optional && variable.default_value.is_none(),
+        ),
+        None if ["string", "number", "boolean", "Date"].contains(&value_type) => {
+            (format!("req.query.{}", variable.name), false)
+        }
+        None => return "req.body".to_string(),
+    };
+
+    let converted = match value_type {
+        "number" => format!("Number({})", value),
+        "boolean" => format!("{} === \"true\"", value),
+        "Date" => format!("new Date({})", value),
+        _ if parameter.is_some() => value.clone(),
+        _ => format!("String({})", value),
+    };
+
+    if required {
+        converted
+    } else {
+        format!("{} === undefined ? {} : {}", value, missing, converted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{express_path, generate_router};
+    use crate::{analyze::tests::analyze_file, analyze_source, route::parse_route, TypeMap};
+
+    #[test]
+    fn express_paths() {
+        let prefix = Some("api/[controller]".to_string());
+        let class_name = Some("TestController".to_string());
+
+        let route = parse_route(&prefix, &None, &class_name, "AddAdmin");
+        assert_eq!(express_path(&route), "/api/Test");
+
+        let route = parse_route(
+            &prefix,
+            &Some("items/{id:int:min(1)}/{page?}/{sort=name}".to_string()),
+            &class_name,
+            "Items",
+        );
+        assert_eq!(express_path(&route), "/api/Test/items/:id/:page?/:sort?");
+
+        let route = parse_route(
+            &prefix,
+            &Some("files/{*path}".to_string()),
+            &class_name,
+            "Files",
+        );
+        assert_eq!(express_path(&route), "/api/Test/files/*path");
+    }
+
+    #[test]
+    fn generate_express_router_input_1() {
+        let data = analyze_file("./tests/mocks/input.cs");
+        let output = generate_router(&data, &TypeMap::default());
+
+        assert!(output.contains("import { Router } from \"express\";"));
+        assert!(output.contains("export const testRouter = Router();"));
+        assert!(output.contains("import { z } from \"zod\";"));
+        assert!(
+            output.contains("import { AdminNameSchema, UserTaskToAddSchema } from \"./schemas\";")
+        );
+        assert!(output.contains(
+            "testRouter.get(\"/api/Test/todoUserTasks/:userOid\", async (req, res) => {\n  const input = z.object({ userOid: z.string().uuid() }).parse({ userOid: req.params.userOid });\n  res.json(await getClientTodoTasks(input.userOid));\n});"
+        ));
+        assert!(output.contains(
+            "testRouter.post(\"/api/Test\", async (req, res) => {\n  const input = z.object({ adminName: AdminNameSchema }).parse({ adminName: req.body });\n  res.json(await addAdmin(input.adminName));\n});"
+        ));
+        assert!(output.contains("testRouter.put(\"/api/Test/completeUserTask/:userTaskOid\""));
+        assert!(output.contains("testRouter.delete(\"/api/Test/userTask/:userTaskOid\""));
+        assert!(output.contains("testRouter.post(\"/api/Test/addUpdateUserTask\""));
+        assert!(output.contains("async function getClientTodoTasks(userOid: string) {"));
+        assert!(output.contains("async function addUserTask(userTaskDetails: UserTaskToAdd) {"));
+
+        assert_eq!(output.matches("async function ").count(), 8);
+        assert_eq!(output.matches("testRouter.").count(), 6);
+    }
+
+    #[test]
+    fn binds_query_values() {
+        let analysis = analyze_source(concat!(
+            "[Route(\"api/[controller]\")]\n",
+            "public class ItemsController : ControllerBase\n",
+            "{\n",
+            "    [HttpGet(\"{id}/{sort?}\")]\n",
+            "    public void Search(int id, string? sort, int? page, string name, bool all)\n",
+            "    {\n",
+            "    }\n",
+            "}\n",
+        ));
+        let output = generate_router(&analysis.data, &TypeMap::default());
+
+        assert!(output.contains(concat!(
+            "parse({ id: Number(req.params.id), ",
+            "sort: req.params.sort === undefined ? null : req.params.sort, ",
+            "page: req.query.page === undefined ? null : Number(req.query.page), ",
+            "name: req.query.name === undefined ? undefined : String(req.query.name), ",
+            "all: req.query.all === undefined ? undefined : req.query.all === \"true\" });"
+        )));
+    }
+
+    #[test]
+    fn matches_route_parameters_ignoring_case() {
+        let analysis = analyze_source(concat!(
+            "[Route(\"api/[controller]\")]\n",
+            "public class ItemsController : ControllerBase\n",
+            "{\n",
+            "    [HttpGet(\"{Id}\")]\n",
+            "    public void Get(int id)\n",
+            "    {\n",
+            "    }\n",
+            "}\n",
+        ));
+        let output = generate_router(&analysis.data, &TypeMap::default());
+
+        assert!(output.contains("itemsRouter.get(\"/api/Items/:Id\""));
+        assert!(output.contains("parse({ id: Number(req.params.Id) });"));
+    }
+
+    #[test]
+    fn leaves_defaulted_values_to_the_schema() {
+        let analysis = analyze_source(concat!(
+            "[Route(\"api/[controller]\")]\n",
+            "public class ItemsController : ControllerBase\n",
+            "{\n",
+            "    [HttpGet]\n",
+            "    public void Search(int page = 1, int? size = 10, string? sort = default)\n",
+            "    {\n",
+            "    }\n",
+            "}\n",
+        ));
+        let output = generate_router(&analysis.data, &TypeMap::default());
+
+        assert!(output.contains(concat!(
+            "z.object({ page: z.number().int().default(1), ",
+            "size: z.number().int().nullable().default(10), ",
+            "sort: z.string().nullable().optional() })"
+        )));
+        assert!(output.contains(concat!(
+            "parse({ page: req.query.page === undefined ? undefined : Number(req.query.page), ",
+            "size: req.query.size === undefined ? undefined : Number(req.query.size), "
+        )));
+    }
+
+    #[test]
+    fn qualifies_functions_of_several_controllers() {
+        let data = analyze_file("./tests/mocks/shop/ShopController.cs");
+        let output = generate_router(&data, &TypeMap::default());
+
+        assert!(output.contains("async function usersGet(id: number) {"));
+        assert!(output.contains("async function ordersGet(id: number) {"));
+        assert!(output.contains("usersRouter.get(\"/api/Users/:id\", async (req, res) => {"));
+        assert!(output.contains("ordersRouter.get(\"/api/Orders/:id\", async (req, res) => {"));
+        assert!(output.contains("res.json(await ordersGet(input.id));"));
+        assert!(!output.contains("function run("));
+    }
+}
@@ -0,0 +1,824 @@
+use crate::lexer::tokenize;
+use crate::{Token, TokenKind};
+
+/// Written after the part of an expression no rule maps, the C# is kept in
+/// front of it so it can be translated by hand.
+pub const TODO_MARKER: &str = "/* TODO: translate */";
+
+/// Static .NET members and calls and the TypeScript they become, `{0}` is
+/// the first argument of a call.
+const STATIC_RULES: &[(&str, &str)] = &[
+    ("Guid.NewGuid()", "crypto.randomUUID()"),
+    ("Guid.Empty", "\"00000000-0000-0000-0000-000000000000\""),
+    ("Guid.Parse({0})", "{0}"),
+    ("DateTime.UtcNow", "new Date()"),
+    ("DateTime.Now", "new Date()"),
+    ("DateTime.Parse({0})", "new Date({0})"),
+    ("String.Empty", "\"\""),
+    ("string.Empty", "\"\""),
+    ("String.IsNullOrEmpty({0})", "!{0}"),
+    ("string.IsNullOrEmpty({0})", "!{0}"),
+    ("String.IsNullOrWhiteSpace({0})", "!{0}?.trim()"),
+    ("string.IsNullOrWhiteSpace({0})", "!{0}?.trim()"),
+    ("String.Join({0}, {1})", "{1}.join({0})"),
+    ("string.Join({0}, {1})", "{1}.join({0})"),
+    ("int.Parse({0})", "Number({0})"),
+    ("Convert.ToInt32({0})", "Number({0})"),
+    ("Math.Abs({0})", "Math.abs({0})"),
+    ("Math.Max({0}, {1})", "Math.max({0}, {1})"),
+    ("Math.Min({0}, {1})", "Math.min({0}, {1})"),
+    ("Math.Round({0})", "Math.round({0})"),
+    ("Math.Floor({0})", "Math.floor({0})"),
+    ("Math.Ceiling({0})", "Math.ceil({0})"),
+];
+
+/// Instance members read after a `.` or `?.`.
+const MEMBER_RULES: &[(&str, &str)] = &[
+    ("HasValue", " != null"),
+    ("ToString()", ".toString()"),
+    ("ToLower()", ".toLowerCase()"),
+    ("ToUpper()", ".toUpperCase()"),
+    ("Trim()", ".trim()"),
+    ("Contains({0})", ".includes({0})"),
+    ("StartsWith({0})", ".startsWith({0})"),
+    ("EndsWith({0})", ".endsWith({0})"),
+    ("Length", ".length"),
+    ("Count", ".length"),
+    ("Count()", ".length"),
+    ("Any()", ".length > 0"),
+];
+
+/// Instance members that only exist in .NET and have no rule.
+const UNMAPPED_MEMBERS: &[&str] = &[
+    "ToList",
+    "ToArray",
+    "ToShortDateString",
+    "ToLongDateString",
+    "AddDays",
+    "AddHours",
+    "AddMinutes",
+    "AddMonths",
+    "AddYears",
+    "GetValueOrDefault",
+    "Value",
+];
+
+/// Types whose static members only exist in .NET, the ones without a rule
+/// are marked.
+const FRAMEWORK_TYPES: &[&str] = &[
+    "Guid",
+    "DateTime",
+    "DateTimeOffset",
+    "TimeSpan",
+    "String",
+    "string",
+    "Math",
+    "Convert",
+    "Enum",
+    "int",
+    "long",
+    "decimal",
+    "double",
+];
+
+/// Casts to these are dropped, `(int)TaskStatuses.Complete` reads the enum
+/// member as it is.
+const CAST_TYPES: &[&str] = &[
+    "int", "long", "short", "byte", "double", "float", "decimal", "string", "bool", "object",
+];
+
+/// Rewrites a C# expression into TypeScript, e.g. `Guid.NewGuid()` becomes
+/// `crypto.randomUUID()`. Parts no rule maps are followed by [`TODO_MARKER`].
+pub fn translate(expression: &str) -> String {
+    let lines = expression
+        .lines()
+        .map(|line| line.to_string())
+        .collect::<Vec<String>>();
+    let tokens = tokenize(&lines);
+    let tokens = tokens
+        .iter()
+        .filter(|token| token.kind != TokenKind::Comment)
+        .collect::<Vec<&Token>>();
+
+    Translator { lines: &lines }.translate(&tokens)
+}
+
+struct Translator<'a> {
+    lines: &'a [String],
+}
+
+impl Translator<'_> {
+    fn translate(&self, tokens: &[&Token]) -> String {
+        if let Some(text) = self.coalesce(tokens) {
+            return text;
+        }
+
+        let mut out = String::new();
+        let mut index = 0;
+
+        while index < tokens.len() {
+            if index > 0 {
+                out.push_str(&self.gap(tokens[index - 1], tokens[index]));
+            }
+
+            let (text, next) = self.translate_at(tokens, index);
+            out.push_str(&text);
+            index = next;
+        }
+
+        out
+    }
+
+    /// The whitespace written between two tokens.
+    fn gap(&self, previous: &Token, next: &Token) -> String {
+        if previous.span.end_line != next.span.start_line {
+            return " ".to_string();
+        }
+
+        self.lines[next.span.start_line as usize]
+            [previous.span.end_column as usize..next.span.start_column as usize]
+            .to_string()
+    }
+
+    fn text(&self, tokens: &[&Token]) -> String {
+        let mut out = String::new();
+
+        for (index, token) in tokens.iter().enumerate() {
+            if index > 0 {
+                out.push_str(&self.gap(tokens[index - 1], token));
+            }
+
+            out.push_str(&token.text);
+        }
+
+        out
+    }
+
+    /// The translated text of the token at `index` and the rules that start
+    /// there, with the index of the token after it.
+    fn translate_at(&self, tokens: &[&Token], index: usize) -> (String, usize) {
+        let token = tokens[index];
+        let is_text = |offset: usize, text: &str| {
+            tokens
+                .get(index + offset)
+                .is_some_and(|token| token.kind != TokenKind::String && token.text == text)
+        };
+
+        if is_text(0, "(")
+            && tokens
+                .get(index + 1)
+                .is_some_and(|token| CAST_TYPES.contains(&token.text.as_str()))
+            && is_text(2, ")")
+            && tokens.get(index + 3).is_some_and(|token| {
+                matches!(token.kind, TokenKind::Identifier | TokenKind::Number) || token.text == "("
+            })
+        {
+            return (String::new(), index + 3);
+        }
+
+        match token.kind {
+            TokenKind::InterpolatedString => return (self.interpolated(&token.text), index + 1),
+            TokenKind::VerbatimString => return (verbatim(&token.text), index + 1),
+            TokenKind::Char => {
+                return (
+                    format!("\"{}\"", token.text.trim_matches('\'').replace('"', "\\\"")),
+                    index + 1,
+                )
+            }
+            _ => (),
+        }
+
+        match token.text.as_str() {
+            "==" | "!=" => {
+                let compares_null =
+                    is_text(1, "null") || (index > 0 && tokens[index - 1].text == "null");
+                let operator = match (compares_null, token.text.as_str()) {
+                    (true, operator) => operator.to_string(),
+                    (false, "==") => "===".to_string(),
+                    _ => "!==".to_string(),
+                };
+
+                return (operator, index + 1);
+            }
+            "!" => {
+                if let Some(end) = operand_end(tokens, index + 1) {
+                    let operand = self.translate(&tokens[index + 1..end]);
+
+                    // `!xs.Any()` is `!(xs.length > 0)`, not `!xs.length > 0`
+                    let atomic = is_atomic(operand.strip_prefix('!').unwrap_or(&operand));
+
+                    return match atomic {
+                        true => (format!("!{}", operand), end),
+                        false => (format!("!({})", operand), end),
+                    };
+                }
+            }
+            "is" if is_text(1, "null") => return ("=== null".to_string(), index + 2),
+            "is" if is_text(1, "not") && is_text(2, "null") => {
+                return ("!== null".to_string(), index + 3)
+            }
+            "is" => {
+                let end = pattern_end(tokens, index + 1);
+
+                return (
+                    format!("{} {}", self.text(&tokens[index..end]), TODO_MARKER),
+                    end,
+                );
+            }
+            "." | "?."
+                if tokens
+                    .get(index + 1)
+                    .is_some_and(|token| token.kind == TokenKind::Identifier) =>
+            {
+                return self.member(tokens, index);
+            }
+            _ => (),
+        }
+
+        let starts_chain = (token.kind == TokenKind::Identifier
+            || (token.kind == TokenKind::Keyword && is_text(1, ".")))
+            && (index == 0 || !matches!(tokens[index - 1].text.as_str(), "." | "?." | "new"));
+
+        if starts_chain {
+            if let Some(result) = self.static_member(tokens, index) {
+                return result;
+            }
+        }
+
+        (token.text.clone(), index + 1)
+    }
+
+    /// `.ToLower()` and the other instance members, accessed with `.` or `?.`.
+    fn member(&self, tokens: &[&Token], index: usize) -> (String, usize) {
+        let accessor = tokens[index].text.as_str();
+        let name = tokens[index + 1].text.as_str();
+        let call = self.call(tokens, index + 2);
+        let next = call.as_ref().map_or(index + 2, |(_, close)| close + 1);
+        let arguments = call.as_ref().map(|(arguments, _)| arguments.as_slice());
+
+        if let Some(template) = find_rule(MEMBER_RULES, name, arguments) {
+            let text = match template.strip_prefix('.') {
+                Some(member) => format!("{}{}", accessor, member),
+                None => template.to_string(),
+            };
+
+            return (text, next);
+        }
+
+        let has_rule = MEMBER_RULES
+            .iter()
+            .any(|(key, _)| rule_shape(key).0 == name);
+
+        // a call of a method without a rule would run a .NET method
+        if has_rule || UNMAPPED_MEMBERS.contains(&name) || call.is_some() {
+            return (
+                format!("{} {}", self.text(&tokens[index..next]), TODO_MARKER),
+                next,
+            );
+        }
+
+        (format!("{}{}", accessor, name), index + 2)
+    }
+
+    /// `Guid.NewGuid()` and the other static members, the longest chain with
+    /// a rule wins.
+    fn static_member(&self, tokens: &[&Token], index: usize) -> Option<(String, usize)> {
+        let mut names = vec![tokens[index].text.clone()];
+        let mut end = index;
+
+        while end + 2 < tokens.len()
+            && tokens[end + 1].text == "."
+            && tokens[end + 2].kind == TokenKind::Identifier
+        {
+            names.push(tokens[end + 2].text.clone());
+            end += 2;
+        }
+
+        for length in (2..=names.len()).rev() {
+            let name = names[..length].join(".");
+            let last = index + (length - 1) * 2;
+            let call = self.call(tokens, last + 1);
+            let next = call.as_ref().map_or(last + 1, |(_, close)| close + 1);
+            let arguments = call.as_ref().map(|(arguments, _)| arguments.as_slice());
+
+            if let (Some(arguments), "String.Format" | "string.Format") = (arguments, name.as_str())
+            {
+                return Some((self.format(tokens, last + 2, arguments), next));
+            }
+
+            if let Some(template) = find_rule(STATIC_RULES, &name, arguments) {
+                return Some((template.to_string(), next));
+            }
+        }
+
+        if names.len() > 1 && FRAMEWORK_TYPES.contains(&names[0].as_str()) {
+            let last = index + 2;
+            let next = self
+                .call(tokens, last + 1)
+                .map_or(last + 1, |(_, close)| close + 1);
+
+            return Some((
+                format!("{} {}", self.text(&tokens[index..next]), TODO_MARKER),
+                next,
+            ));
+        }
+
+        None
+    }
+
+    /// The translated arguments of a call whose `(` is at `open`, with the
+    /// index of its `)`.
+    fn call(&self, tokens: &[&Token], open: usize) -> Option<(Vec<String>, usize)> {
+        if tokens.get(open)?.text != "(" {
+            return None;
+        }
+
+        let mut depth = 0;
+        let mut arguments = vec![];
+        let mut start = open + 1;
+
+        for index in open..tokens.len() {
+            match tokens[index].text.as_str() {
+                "(" | "[" | "{" => depth += 1,
+                ")" | "]" | "}" => {
+                    depth -= 1;
+
+                    if depth == 0 {
+                        if index > start {
+                            arguments.push(self.translate(&tokens[start..index]));
+                        }
+
+                        return Some((arguments, index));
+                    }
+                }
+                "," if depth == 1 => {
+                    arguments.push(self.translate(&tokens[start..index]));
+                    start = index + 1;
+                }
+                _ => (),
+            }
+        }
+
+        None
+    }
+
+    /// `String.Format("{0} items", count)` as the template literal
+    /// `` `${count} items` ``.
+    fn format(&self, tokens: &[&Token], first: usize, arguments: &[String]) -> String {
+        let format = match tokens.get(first) {
+            Some(token) if token.kind == TokenKind::String => token.text.trim_matches('"'),
+            _ => return format!("String.Format({}) {}", arguments.join(", "), TODO_MARKER),
+        };
+
+        template_literal(format, |hole| {
+            let (position, format) = split_format(hole);
+
+            match position.trim().parse::<usize>() {
+                Ok(position) if position + 1 < arguments.len() => {
+                    (arguments[position + 1].clone(), format.is_some())
+                }
+                _ => (hole.to_string(), true),
+            }
+        })
+    }
+
+    /// `$"{count} items"` as the template literal `` `${count} items` ``.
+    fn interpolated(&self, text: &str) -> String {
+        let verbatim = text.starts_with("$@") || text.starts_with("@$");
+        let content = text.trim_start_matches(['$', '@']);
+        let content = &content[1..content.len().saturating_sub(1).max(1)];
+        let content = if verbatim {
+            content.replace("\"\"", "\"").replace('\\', "\\\\")
+        } else {
+            content.to_string()
+        };
+
+        template_literal(&content, |hole| {
+            let (expression, format) = split_format(hole);
+
+            (translate(expression), format.is_some())
+        })
+    }
+
+    /// `x.HasValue ? x.Value : y` reads `x` when it is set, which is `x ?? y`.
+    /// Any other use of `x.Value` is guarded with a ternary and reads `x`.
+    fn coalesce(&self, tokens: &[&Token]) -> Option<String> {
+        let question = top_level_position(tokens, "?")?;
+        let colon = question + top_level_position(&tokens[question..], ":")?;
+
+        let (has_value, subject) = tokens[..question].split_last()?;
+        let (dot, subject) = subject.split_last()?;
+
+        if has_value.text != "HasValue" || dot.text != "." || subject.is_empty() {
+            return None;
+        }
+
+        let when_set = &tokens[question + 1..colon];
+        let otherwise = self.translate(&tokens[colon + 1..]);
+
+        if when_set.len() == subject.len() + 2 && reads_value(when_set, 0, subject)
+            || same_text(when_set, subject)
+        {
+            return Some(format!("{} ?? {}", self.translate(subject), otherwise));
+        }
+
+        if !(0..when_set.len()).any(|index| reads_value(when_set, index, subject)) {
+            return None;
+        }
+
+        Some(format!(
+            "{} != null ? {} : {}",
+            self.translate(subject),
+            self.without_value(when_set, subject),
+            otherwise
+        ))
+    }
+
+    /// Translates `tokens` with every `subject.Value` read as `subject`.
+    fn without_value(&self, tokens: &[&Token], subject: &[&Token]) -> String {
+        let mut out = String::new();
+        let mut gap = String::new();
+        let mut start = 0;
+        let mut index = 0;
+
+        while index < tokens.len() {
+            if !reads_value(tokens, index, subject) {
+                index += 1;
+                continue;
+            }
+
+            if index > start {
+                out.push_str(&gap);
+                out.push_str(&self.translate(&tokens[start..index]));
+                out.push_str(&self.gap(tokens[index - 1], tokens[index]));
+            }
+
+            out.push_str(&self.translate(subject));
+            index += subject.len() + 2;
+            start = index;
+            gap = match tokens.get(index) {
+                Some(next) => self.gap(tokens[index - 1], next),
+                None => String::new(),
+            };
+        }
+
+        if start < tokens.len() {
+            out.push_str(&gap);
+            out.push_str(&self.translate(&tokens[start..]));
+        }
+
+        out
+    }
+}
+
+/// Whether `subject.Value` starts at `index`.
+fn reads_value(tokens: &[&Token], index: usize, subject: &[&Token]) -> bool {
+    let end = index + subject.len();
+
+    tokens.len() >= end + 2
+        && same_text(&tokens[index..end], subject)
+        && tokens[end].text == "."
+        && tokens[end + 1].text == "Value"
+}
+
+fn same_text(tokens: &[&Token], other: &[&Token]) -> bool {
+    tokens.len() == other.len()
+        && tokens
+            .iter()
+            .zip(other)
+            .all(|(token, other)| token.text == other.text)
+}
+
+/// Splits a rule key like `Contains({0})` into the member name and the
+/// number of arguments, properties have no argument list.
+fn rule_shape(key: &str) -> (&str, Option<usize>) {
+    match key.split_once('(') {
+        Some((name, arguments)) => (name, Some(arguments.matches('{').count())),
+        None => (key, None),
+    }
+}
+
+fn find_rule(rules: &[(&str, &str)], name: &str, arguments: Option<&[String]>) -> Option<String> {
+    let (_, template) = rules
+        .iter()
+        .find(|(key, _)| rule_shape(key) == (name, arguments.map(|arguments| arguments.len())))?;
+
+    let mut text = template.to_string();
+
+    for (position, argument) in arguments.unwrap_or_default().iter().enumerate() {
+        let placeholder = format!("{{{}}}", position);
+
+        // `!{0}` and `{0}.join` bind tighter than an operator in the argument
+        let is_operand = template.contains(&format!("!{}", placeholder))
+            || template.contains(&format!("{}.", placeholder))
+            || template.contains(&format!("{}?.", placeholder));
+
+        let argument = if is_operand && !is_atomic(argument) {
+            format!("({})", argument)
+        } else {
+            argument.clone()
+        };
+
+        text = text.replace(&placeholder, &argument);
+    }
+
+    Some(text)
+}
+
+/// Whether an expression can have a `!` in front or a member after it
+/// without parentheses, like `name`, `user.Name` or `items.map(x)`.
+fn is_atomic(expression: &str) -> bool {
+    let chars = expression.chars().collect::<Vec<char>>();
+    let mut depth = 0;
+    let mut quote = None;
+
+    for (index, char) in chars.iter().enumerate() {
+        let escaped = index > 0 && chars[index - 1] == '\\';
+
+        match (quote, char) {
+            (Some(open), char) if *char == open && !escaped => quote = None,
+            (Some(_), _) => (),
+            (None, '"' | '\'' | '`') => quote = Some(*char),
+            (None, '(' | '[' | '{') => depth += 1,
+            (None, ')' | ']' | '}') => depth -= 1,
+            (None, '?') if chars.get(index + 1) == Some(&'.') => (),
+            (None, char)
+                if depth == 0 && (char.is_whitespace() || "+-*/%<>=!&|?:,".contains(*char)) =>
+            {
+                return false
+            }
+            _ => (),
+        }
+    }
+
+    true
+}
+
+/// The index after the operand that starts at `start`, a member chain like
+/// `user.Tasks.Any()` or a parenthesized expression.
+fn operand_end(tokens: &[&Token], start: usize) -> Option<usize> {
+    let first = tokens.get(start)?;
+    let mut end = match first.text.as_str() {
+        "(" => closing_bracket(tokens, start)? + 1,
+        _ if matches!(first.kind, TokenKind::Identifier | TokenKind::Keyword) => start + 1,
+        _ => return None,
+    };
+
+    loop {
+        match tokens.get(end).map(|token| token.text.as_str()) {
+            Some("." | "?.")
+                if tokens
+                    .get(end + 1)
+                    .is_some_and(|token| token.kind == TokenKind::Identifier) =>
+            {
+                end += 2
+            }
+            Some("(" | "[") => end = closing_bracket(tokens, end)? + 1,
+            _ => return Some(end),
+        }
+    }
+}
+
+/// The index after a pattern like `> 3 and < 10` or `string name`, which
+/// ends where the expression around it goes on.
+fn pattern_end(tokens: &[&Token], start: usize) -> usize {
+    let mut depth = 0;
+
+    for (index, token) in tokens.iter().enumerate().skip(start) {
+        match token.text.as_str() {
+            "(" | "[" | "{" => depth += 1,
+            ")" | "]" | "}" if depth == 0 => return index,
+            ")" | "]" | "}" => depth -= 1,
+            "&&" | "||" | "?" | ":" | "," | ";" if depth == 0 => return index,
+            _ => (),
+        }
+    }
+
+    tokens.len()
+}
+
+/// The index of the bracket closing the one opened at `open`.
+fn closing_bracket(tokens: &[&Token], open: usize) -> Option<usize> {
+    let mut depth = 0;
+
+    for (index, token) in tokens.iter().enumerate().skip(open) {
+        match token.text.as_str() {
+            "(" | "[" | "{" => depth += 1,
+            ")" | "]" | "}" => {
+                depth -= 1;
+
+                if depth == 0 {
+                    return Some(index);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    None
+}
+
+fn top_level_position(tokens: &[&Token], text: &str) -> Option<usize> {
+    let mut depth = 0;
+
+    for (index, token) in tokens.iter().enumerate() {
+        match token.text.as_str() {
+            "(" | "[" | "{" => depth += 1,
+            ")" | "]" | "}" => depth -= 1,
+            _ if depth == 0 && token.text == text && token.kind == TokenKind::Punctuation => {
+                return Some(index)
+            }
+            _ => (),
+        }
+    }
+
+    None
+}
+
+/// Splits `{value:yyyy-MM-dd}` into the value and its format.
+fn split_format(hole: &str) -> (&str, Option<&str>) {
+    let mut depth = 0;
+
+    for (index, char) in hole.char_indices() {
+        match char {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ':' | ',' if depth == 0 => return (&hole[..index], Some(&hole[index + 1..])),
+            _ => (),
+        }
+    }
+
+    (hole, None)
+}
+
+/// Writes the text of a format string as a template literal, each `{hole}`
+/// is replaced by the expression `fill` returns and marked when it has a
+/// format the literal cannot apply.
+fn template_literal(content: &str, fill: impl Fn(&str) -> (String, bool)) -> String {
+    let mut out = "`".to_string();
+    let mut chars = content.chars().peekable();
+    let mut marked = false;
+
+    while let Some(char) = chars.next() {
+        match char {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                out.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                out.push('}');
+            }
+            '{' => {
+                let mut hole = String::new();
+                let mut depth = 1;
+
+                for char in chars.by_ref() {
+                    match char {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        _ => (),
+                    }
+
+                    if depth == 0 {
+                        break;
+                    }
+
+                    hole.push(char);
+                }
+
+                let (expression, has_format) = fill(&hole);
+                marked |= has_format;
+                out.push_str(&format!("${{{}}}", expression));
+            }
+            '`' => out.push_str("\\`"),
+            '$' if chars.peek() == Some(&'{') => out.push_str("\\$"),
+            _ => out.push(char),
+        }
+    }
+
+    out.push('`');
+
+    if marked {
+        out.push(' ');
+        out.push_str(TODO_MARKER);
+    }
+
+    out
+}
+
+/// `@"c:\temp"` as `"c:\\temp"`.
+fn verbatim(text: &str) -> String {
+    let content = text.trim_start_matches('@');
+    let content = &content[1..content.len().saturating_sub(1).max(1)];
+
+    format!(
+        "\"{}\"",
+        content
+            .replace("\"\"", "\"")
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{translate, TODO_MARKER};
+
+    #[test]
+    fn translates_common_expressions() {
+        assert_eq!(translate("Guid.NewGuid()"), "crypto.randomUUID()");
+        assert_eq!(translate("DateTime.UtcNow"), "new Date()");
+        assert_eq!(
+            translate("userTaskDetails.UserTaskOid == Guid.Empty"),
+            "userTaskDetails.UserTaskOid === \"00000000-0000-0000-0000-000000000000\""
+        );
+        assert_eq!(translate("userTask != null"), "userTask != null");
+        assert_eq!(
+            translate("x is not null && y.HasValue"),
+            "x !== null && y != null"
+        );
+        assert_eq!(
+            translate("(int)TaskStatuses.Complete"),
+            "TaskStatuses.Complete"
+        );
+        assert_eq!(
+            translate("String.Format(\"Could not login, error: {0}\", ex.Message)"),
+            "`Could not login, error: ${ex.Message}`"
+        );
+        assert_eq!(
+            translate("$\"{user.Name.ToLower()} has {tasks.Count} tasks\""),
+            "`${user.Name.toLowerCase()} has ${tasks.length} tasks`"
+        );
+        assert_eq!(
+            translate("ut.CompleteDate.HasValue ? ut.CompleteDate.Value : null"),
+            "ut.CompleteDate ?? null"
+        );
+        assert_eq!(translate("a.HasValue ? a : 0"), "a ?? 0");
+        assert_eq!(
+            translate("a.HasValue ? a.Value.ToString() == s : s == \"\""),
+            "a != null ? a.toString() === s : s === \"\""
+        );
+        assert_eq!(
+            translate("a.HasValue ? a.Value + a.Value : 0"),
+            "a != null ? a + a : 0"
+        );
+        assert_eq!(
+            translate("string.IsNullOrEmpty(name) ? \"-\" : name.Trim()"),
+            "!name ? \"-\" : name.trim()"
+        );
+        assert_eq!(translate("@\"c:\\temp\""), "\"c:\\\\temp\"");
+        assert_eq!(translate("$\"mail {b}@x.com\\t\""), "`mail ${b}@x.com\\t`");
+        assert_eq!(translate("$@\"c:\\{dir}\""), "`c:\\\\${dir}`");
+        assert_eq!(translate("string.IsNullOrEmpty(a + b)"), "!(a + b)");
+        assert_eq!(translate("string.IsNullOrEmpty(user.Name)"), "!user.Name");
+        assert_eq!(
+            translate("string.Join(\", \", first ?? rest)"),
+            "(first ?? rest).join(\", \")"
+        );
+        assert_eq!(translate("!items.Any()"), "!(items.length > 0)");
+        assert_eq!(translate("!user.Date.HasValue"), "!(user.Date != null)");
+        assert_eq!(translate("!string.IsNullOrEmpty(name)"), "!!name");
+        assert_eq!(translate("!ok && !(a || b)"), "!ok && !(a || b)");
+    }
+
+    #[test]
+    fn marks_what_it_cannot_map() {
+        assert_eq!(
+            translate(
+                "ut.CompleteDate.HasValue ? ut.CompleteDate.Value.ToString(\"yyyy-MM-dd HH:mm:ss\") : \"\""
+            ),
+            format!(
+                "ut.CompleteDate != null ? ut.CompleteDate.ToString(\"yyyy-MM-dd HH:mm:ss\") {} : \"\"",
+                TODO_MARKER
+            )
+        );
+        assert_eq!(
+            translate("DateTime.Today.AddDays(1)"),
+            format!("DateTime.Today {}.AddDays(1) {}", TODO_MARKER, TODO_MARKER)
+        );
+        assert_eq!(
+            translate("$\"{total:N2}\""),
+            format!("`${{total}}` {}", TODO_MARKER)
+        );
+        assert_eq!(
+            translate("enc.Encrypt(a, b)"),
+            format!("enc.Encrypt(a, b) {}", TODO_MARKER)
+        );
+        assert_eq!(
+            translate("s.Equals(x, StringComparison.OrdinalIgnoreCase)"),
+            format!(
+                "s.Equals(x, StringComparison.OrdinalIgnoreCase) {}",
+                TODO_MARKER
+            )
+        );
+        assert_eq!(
+            translate("a.Value + 1"),
+            format!("a.Value {} + 1", TODO_MARKER)
+        );
+        assert_eq!(
+            translate("a is > 3 && b"),
+            format!("a is > 3 {} && b", TODO_MARKER)
+        );
+    }
+}
@@ -0,0 +1,653 @@
+use crate::analyze::{declaration_start, enclosing_class, is_controller};
+use crate::types::ts_type;
+use crate::{Assignment, Block, BlockDetails, BlockType, Data, HttpType, Model, TypeMap, Variable};
+use expression::translate;
+
+mod express;
+mod expression;
+mod models;
+mod module;
+mod prisma;
+mod trpc;
+mod zod;
+
+const INDENT: &str = "  ";
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum Target {
+    Express,
+    Module,
+    Trpc,
+}
+
+pub fn get_target_from_string(str: &str) -> Option<Target> {
+    match str {
+        "express" => Some(Target::Express),
+        "module" => Some(Target::Module),
+        "trpc" => Some(Target::Trpc),
+        _ => None,
+    }
+}
+
+pub fn generate(data: &Data, target: &Target, types: &TypeMap) -> String {
+    match target {
+        Target::Express => express::generate_router(data, types),
+        Target::Module => module::generate_router(data, types),
+        Target::Trpc => trpc::generate_router(data, types),
+    }
+}
+
+/// The router that merges the routers of a batch, each given as the module it
+/// is written to, e.g. `TestController`, and the router that module exports.
+pub fn generate_index(routers: &[(String, String)], target: &Target) -> String {
+    match target {
+        Target::Express => express::generate_index(routers),
+        Target::Module => module::generate_index(routers),
+        Target::Trpc => trpc::generate_index(routers),
+    }
+}
+
+/// The interfaces and zod schemas of the DTOs the routers use.
+pub fn generate_models(models: &[Model], types: &TypeMap) -> String {
+    models::generate_models(models, types)
+}
+
+/// The routers a file is converted to, one per controller class.
+pub fn router_names(data: &Data) -> Vec<String> {
+    controllers(data)
+        .iter()
+        .map(|controller| router_name(&controller.class_name))
+        .collect()
+}
+
+pub fn router_name(class_name: &Option<String>) -> String {
+    let class_name = class_name.clone().unwrap_or_else(|| "App".to_string());
+    let base_name = class_name.trim_end_matches("Controller");
+
+    format!("{}Router", lower_first(base_name))
+}
+
+/// Words TypeScript does not allow as the name of a function.
+const RESERVED_WORDS: &[&str] = &[
+    "arguments",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "eval",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+/// The name an action is written under, `GetItems` becomes `getItems`. Names
+/// that are reserved in TypeScript get a `_` after them, `Delete` becomes
+/// `delete_`.
+pub fn procedure_name(method_name: &str) -> String {
+    let name = lower_first(method_name);
+
+    match RESERVED_WORDS.contains(&name.as_str()) {
+        true => format!("{}_", name),
+        false => name,
+    }
+}
+
+/// The name of a method written as a module level function. Several routers
+/// in one module qualify it with their controller, `Get` of `UsersController`
+/// becomes `usersGet`.
+fn function_name(data: &Data, method_name: &str, qualified: bool) -> String {
+    match &data.class_name {
+        Some(class_name) if qualified => format!(
+            "{}{}",
+            lower_first(class_name.trim_end_matches("Controller")),
+            method_name
+        ),
+        _ => procedure_name(method_name),
+    }
+}
+
+/// The key a router is merged under in the index, `testRouter` becomes `test`.
+fn index_key(router: &str) -> &str {
+    router.strip_suffix("Router").unwrap_or(router)
+}
+
+fn lower_first(value: &str) -> String {
+    let mut chars = value.chars();
+
+    match chars.next() {
+        Some(first) => first.to_lowercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+/// Every controller class of a file with the method blocks declared in it,
+/// methods of other classes are left out. A file without controllers is read
+/// as one.
+fn controllers(data: &Data) -> Vec<Data> {
+    let controllers = data
+        .classes
+        .iter()
+        .filter(|class| is_controller(class))
+        .collect::<Vec<_>>();
+
+    if controllers.is_empty() {
+        return vec![data.clone()];
+    }
+
+    controllers
+        .into_iter()
+        .map(|controller| Data {
+            class_name: Some(controller.name.clone()),
+            route_prefix: controller.route_prefix.clone(),
+            class_attributes: controller.attributes.clone(),
+            blocks: data.blocks.as_ref().map(|blocks| {
+                blocks
+                    .iter()
+                    .filter(|block| {
+                        block.block_type != BlockType::Method
+                            || enclosing_class(&data.classes, block.start)
+                                .is_some_and(|class| class.start == controller.start)
+                    })
+                    .cloned()
+                    .collect()
+            }),
+            ..data.clone()
+        })
+        .collect()
+}
+
+/// The blocks of all controllers, which the imports of a file are read from.
+fn controller_blocks(controllers: &[Data]) -> Vec<Block> {
+    let mut blocks: Vec<Block> = vec![];
+
+    for block in controllers
+        .iter()
+        .flat_map(|controller| controller.blocks.iter().flatten())
+    {
+        if !blocks.contains(block) {
+            blocks.push(block.clone());
+        }
+    }
+
+    blocks
+}
+
+fn write_header(controllers: &[Data], out: &mut String) {
+    let names = controllers
+        .iter()
+        .filter_map(|controller| controller.class_name.clone())
+        .collect::<Vec<String>>();
+
+    if !names.is_empty() {
+        out.push_str(&format!(
+            "// Generated from {} by controller_to_router_rs\n\n",
+            names.join(", ")
+        ));
+    }
+}
+
+fn write_index_imports(routers: &[(String, String)], out: &mut String) {
+    for (module, router) in routers {
+        out.push_str(&format!("import {{ {} }} from \"./{}\";\n", router, module));
+    }
+}
+
+/// Parameters of DTO types are typed with the interfaces in `schemas.ts`.
+fn write_type_import(blocks: &[Block], types: &TypeMap, out: &mut String) {
+    let variables = method_blocks(blocks)
+        .into_iter()
+        .flat_map(|block| match &block.details {
+            Some(BlockDetails::MethodBlock { variables, .. }) => variables.iter().collect(),
+            _ => vec![],
+        })
+        .collect::<Vec<&Variable>>();
+
+    let names = zod::schema_references(&variables, types)
+        .iter()
+        .filter_map(|schema| schema.strip_suffix("Schema"))
+        .map(|name| name.to_string())
+        .collect::<Vec<String>>();
+
+    if !names.is_empty() {
+        out.push_str(&format!(
+            "import type {{ {} }} from \"./schemas\";\n",
+            names.join(", ")
+        ));
+    }
+}
+
+/// Methods that open a database context get a Prisma client in place of it.
+fn write_prisma_import(blocks: &[Block], out: &mut String) {
+    let uses_context = blocks.iter().any(|block| {
+        matches!(
+            &block.details,
+            Some(BlockDetails::MethodBlock {
+                uses_context: true,
+                ..
+            })
+        )
+    });
+
+    if uses_context {
+        out.push_str("import { prisma } from \"../db\";\n");
+    }
+}
+
+fn method_blocks(blocks: &[Block]) -> Vec<&Block> {
+    blocks
+        .iter()
+        .filter(|block| block.block_type == BlockType::Method)
+        .collect::<Vec<&Block>>()
+}
+
+fn http_method(block: &Block) -> Option<HttpType> {
+    match &block.details {
+        Some(BlockDetails::MethodBlock { http_method, .. }) => http_method.clone(),
+        _ => None,
+    }
+}
+
+/// The parameters of every action, which the router validates its input with.
+fn input_variables(blocks: &[Block]) -> Vec<&Variable> {
+    method_blocks(blocks)
+        .into_iter()
+        .filter(|block| http_method(block).is_some())
+        .flat_map(|block| match &block.details {
+            Some(BlockDetails::MethodBlock { variables, .. }) => variables.iter().collect(),
+            _ => vec![],
+        })
+        .collect()
+}
+
+/// Controller methods without an http attribute are only called from other
+/// methods, so they are emitted as module level functions next to the router.
+fn write_helper_functions(
+    data: &Data,
+    blocks: &[Block],
+    types: &TypeMap,
+    qualified: bool,
+    out: &mut String,
+) {
+    for method in method_blocks(blocks)
+        .iter()
+        .filter(|block| http_method(block).is_none())
+    {
+        write_function(data, blocks, types, method, qualified, out);
+    }
+}
+
+fn write_function(
+    data: &Data,
+    blocks: &[Block],
+    types: &TypeMap,
+    method: &Block,
+    qualified: bool,
+    out: &mut String,
+) {
+    if let Some(BlockDetails::MethodBlock {
+        name, variables, ..
+    }) = &method.details
+    {
+        out.push_str(&format!(
+            "async function {}({}) {{\n",
+            function_name(data, name, qualified),
+            parameters(variables, types)
+        ));
+        write_body(data, blocks, types, method, 1, out);
+        out.push_str("}\n\n");
+    }
+}
+
+fn parameters(variables: &[Variable], types: &TypeMap) -> String {
+    variables
+        .iter()
+        .map(|variable| {
+            format!(
+                "{}: {}",
+                variable.name,
+                ts_type(types, &variable.variable_type)
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn is_brace_block(data: &Data, block: &Block) -> bool {
+    data.lines[block.start as usize]
+        .trim_start()
+        .starts_with('{')
+}
+
+fn block_end(block: &Block) -> u32 {
+    block.end.unwrap_or(block.start)
+}
+
+/// The first and last source line a block occupies, including the
+/// declaration line(s) in front of its opening brace.
+fn block_span(data: &Data, block: &Block) -> (u32, u32) {
+    let end = block_end(block);
+
+    if !is_brace_block(data, block) {
+        return (block.start, end);
+    }
+
+    if block.block_type != BlockType::Select {
+        return (declaration_start(data, block), end);
+    }
+
+    let keywords = ["from", "join", "where", "orderby", "select", "&&"];
+    let mut first = block.start.saturating_sub(1);
+
+    while first > 0
+        && keywords
+            .iter()
+            .any(|word| data.lines[first as usize - 1].contains(word))
+    {
+        first -= 1;
+    }
+
+    let last = if data.lines[end as usize].contains(';') {
+        end
+    } else {
+        end + 1
+    };
+
+    (first, last)
+}
+
+fn child_blocks<'a>(data: &Data, blocks: &'a [Block], parent: &Block) -> Vec<&'a Block> {
+    let parent_end = block_end(parent);
+    let inside = |outer: &Block, inner: &Block| {
+        outer.start < inner.start && block_end(inner) <= block_end(outer)
+    };
+
+    let mut children = blocks
+        .iter()
+        .filter(|block| block.start > parent.start && block_end(block) < parent_end)
+        .filter(|block| {
+            !blocks.iter().any(|other| {
+                other.start > parent.start && inside(other, block) && inside(parent, other)
+            })
+        })
+        .collect::<Vec<&Block>>();
+
+    children.sort_by_key(|block| block_span(data, block).0);
+
+    children
+}
+
+/// The variable name of a `var name = ...` statement.
+fn assigned_variable(line: &str) -> Option<String> {
+    let (declaration, _) = line.split_once('=')?;
+    let parts = declaration.split_whitespace().collect::<Vec<&str>>();
+
+    match parts.as_slice() {
+        [_, name] => Some(name.to_string()),
+        _ => None,
+    }
+}
+
+fn push_line(out: &mut String, indent: usize, text: &str) {
+    out.push_str(&INDENT.repeat(indent));
+    out.push_str(text);
+    out.push('\n');
+}
+
+fn write_todo_lines(data: &Data, first: u32, last: u32, indent: usize, out: &mut String) {
+    for index in first..=last {
+        let line = data.lines[index as usize].trim();
+
+        if !line.is_empty() {
+            push_line(out, indent, &format!("// TODO: {}", line));
+        }
+    }
+}
+
+fn write_assignment(assignment: &Assignment, indent: usize, out: &mut String) {
+    push_line(
+        out,
+        indent,
+        &format!("{}: {},", assignment.property, translate(&assignment.value)),
+    );
+}
+
+fn write_body(
+    data: &Data,
+    blocks: &[Block],
+    types: &TypeMap,
+    parent: &Block,
+    indent: usize,
+    out: &mut String,
+) {
+    let children = child_blocks(data, blocks, parent);
+    let mut index = parent.start + 1;
+
+    while index < block_end(parent) {
+        let child = children
+            .iter()
+            .find(|child| block_span(data, child).0 == index);
+
+        if let Some(child) = child {
+            write_block(data, blocks, types, child, indent, out);
+            index = block_span(data, child).1 + 1;
+            continue;
+        }
+
+        write_todo_lines(data, index, index, indent, out);
+        index += 1;
+    }
+}
+
+fn write_block(
+    data: &Data,
+    blocks: &[Block],
+    types: &TypeMap,
+    block: &Block,
+    indent: usize,
+    out: &mut String,
+) {
+    let (first, last) = block_span(data, block);
+    let header = data.lines[first as usize].trim();
+
+    match &block.details {
+        Some(BlockDetails::ContextBlock { .. }) => {
+            write_body(data, blocks, types, block, indent, out);
+        }
+        Some(BlockDetails::IfBlock { clause, is_else }) => {
+            let clause = translate(clause);
+            let statement = match (is_else, clause.is_empty()) {
+                (true, true) => "else {".to_string(),
+                (true, false) => format!("else if ({}) {{", clause),
+                _ => format!("if ({}) {{", clause),
+            };
+
+            push_line(out, indent, &statement);
+            write_body(data, blocks, types, block, indent + 1, out);
+            push_line(out, indent, "}");
+        }
+        Some(BlockDetails::VariableBlock {
+            name,
+            data_type,
+            assignments,
+        }) => {
+            let declaration = if data_type == "var" {
+                format!("const {} = {{", name)
+            } else {
+                format!("const {}: {} = {{", name, ts_type(types, data_type))
+            };
+
+            push_line(out, indent, &declaration);
+
+            if assignments.is_empty()
+                || assignments
+                    .iter()
+                    .any(|assignment| assignment.value.contains('\n'))
+            {
+                write_body(data, blocks, types, block, indent + 1, out);
+            } else {
+                for assignment in assignments {
+                    write_assignment(assignment, indent + 1, out);
+                }
+            }
+
+            push_line(out, indent, "};");
+        }
+        Some(details @ BlockDetails::SelectBlock { has_return, .. }) => {
+            if let Some(query) = prisma::select_query(details) {
+                let statement = if *has_return {
+                    format!("return await {};", query)
+                } else {
+                    match assigned_variable(header) {
+                        Some(name) => format!("const {} = await {};", name, query),
+                        None => format!("await {};", query),
+                    }
+                };
+
+                for line in statement.lines() {
+                    push_line(out, indent, line);
+                }
+            } else {
+                write_todo_lines(data, first, last, indent, out);
+            }
+        }
+        Some(
+            details @ (BlockDetails::InsertBlock { .. }
+            | BlockDetails::DeleteBlock { .. }
+            | BlockDetails::UpdateBlock { .. }),
+        ) => match prisma::write_query(details) {
+            Some(query) => {
+                for line in format!("await {};", query).lines() {
+                    push_line(out, indent, line);
+                }
+            }
+            None => write_todo_lines(data, first, last, indent, out),
+        },
+        // Prisma writes every change at once, there is nothing left to save.
+        Some(BlockDetails::SaveChangesBlock { .. }) => (),
+        _ if header.starts_with("try") || header.starts_with("finally") => {
+            push_line(out, indent, &format!("{} {{", header));
+            write_body(data, blocks, types, block, indent + 1, out);
+            push_line(out, indent, "}");
+        }
+        _ if header.starts_with("catch") => {
+            let exception = header
+                .trim_start_matches("catch")
+                .trim()
+                .trim_start_matches('(')
+                .trim_end_matches(')')
+                .split(' ')
+                .nth(1)
+                .map(|name| name.to_string());
+
+            let statement = match exception {
+                Some(name) => format!("catch ({}) {{", name),
+                None => "catch {".to_string(),
+            };
+
+            push_line(out, indent, &statement);
+            write_body(data, blocks, types, block, indent + 1, out);
+            push_line(out, indent, "}");
+        }
+        _ => {
+            write_todo_lines(data, first, block.start.saturating_sub(1), indent, out);
+            push_line(out, indent, "{");
+            write_body(data, blocks, types, block, indent + 1, out);
+            push_line(out, indent, "}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_index, get_target_from_string, procedure_name, router_name, Target};
+
+    #[test]
+    fn router_name_strips_controller_suffix() {
+        assert_eq!(
+            router_name(&Some("TestController".to_string())),
+            "testRouter"
+        );
+        assert_eq!(router_name(&Some("Users".to_string())), "usersRouter");
+        assert_eq!(router_name(&None), "appRouter");
+    }
+
+    #[test]
+    fn procedure_name_escapes_reserved_words() {
+        assert_eq!(procedure_name("GetItems"), "getItems");
+        assert_eq!(procedure_name("Delete"), "delete_");
+        assert_eq!(procedure_name("Import"), "import_");
+        assert_eq!(procedure_name("New"), "new_");
+        assert_eq!(procedure_name("Default"), "default_");
+    }
+
+    #[test]
+    fn target_from_string() {
+        assert_eq!(get_target_from_string("trpc"), Some(Target::Trpc));
+        assert_eq!(get_target_from_string("module"), Some(Target::Module));
+        assert_eq!(get_target_from_string("express"), Some(Target::Express));
+        assert_eq!(get_target_from_string("graphql"), None);
+    }
+
+    #[test]
+    fn index_merges_routers() {
+        let routers = vec![
+            ("TestController".to_string(), "testRouter".to_string()),
+            ("UsersController".to_string(), "usersRouter".to_string()),
+        ];
+
+        let trpc = generate_index(&routers, &Target::Trpc);
+        assert!(trpc.contains("import { testRouter } from \"./TestController\";\n"));
+        assert!(trpc.contains(
+            "export const appRouter = router({\n  test: testRouter,\n  users: usersRouter,\n});"
+        ));
+        assert!(trpc.ends_with("export type AppRouter = typeof appRouter;\n"));
+
+        let express = generate_index(&routers, &Target::Express);
+        assert!(express.contains("import { usersRouter } from \"./UsersController\";\n"));
+        assert!(express.ends_with("appRouter.use(testRouter);\nappRouter.use(usersRouter);\n"));
+
+        let module = generate_index(&routers, &Target::Module);
+        assert!(module.ends_with(
+            "export const appRouter = {\n  test: testRouter,\n  users: usersRouter,\n};\n"
+        ));
+    }
+}
@@ -0,0 +1,206 @@
+use super::INDENT;
+use crate::types::{ts_type, zod_type};
+use crate::{Model, TypeMap};
+
+/// An interface and a zod schema checked against it for every model. Schemas
+/// are written after the schemas they refer to, so they can be used at once.
+/// Models that refer to each other or to themselves are read lazily, and
+/// types that are not modeled, like enums, are left as `unknown`.
+pub fn generate_models(models: &[Model], types: &TypeMap) -> String {
+    let mut out = String::new();
+    let ordered = dependency_order(models);
+
+    out.push_str("// Generated by controller_to_router_rs\n\n");
+    out.push_str("import { z } from \"zod\";\n");
+
+    for (position, model) in ordered.iter().enumerate() {
+        let fields = model
+            .properties
+            .iter()
+            .map(|property| {
+                let mut ts = ts_type(types, &property.property_type);
+                let mut zod = zod_type(types, &property.property_type);
+                let mut todo = String::new();
+                let mut lazy = false;
+
+                for reference in schema_names(&zod) {
+                    match ordered.iter().position(|model| model.name == reference) {
+                        Some(declared) => lazy = lazy || declared >= position,
+                        None => {
+                            ts = replace_word(&ts, &reference, "unknown");
+                            zod =
+                                replace_word(&zod, &format!("{}Schema", reference), "z.unknown()");
+                            todo = format!(" // TODO: {} is not modeled", reference);
+                        }
+                    }
+                }
+
+                if lazy {
+                    zod = format!("z.lazy(() => {})", zod);
+                }
+
+                (&property.name, ts, zod, todo)
+            })
+            .collect::<Vec<_>>();
+
+        out.push_str(&format!("\nexport interface {} {{\n", model.name));
+
+        for (name, ts, _, todo) in &fields {
+            out.push_str(&format!("{}{}: {};{}\n", INDENT, name, ts, todo));
+        }
+
+        out.push_str("}\n\n");
+        out.push_str(&format!(
+            "export const {0}Schema: z.ZodType<{0}> = z.object({{\n",
+            model.name
+        ));
+
+        for (name, _, zod, todo) in &fields {
+            out.push_str(&format!("{}{}: {},{}\n", INDENT, name, zod, todo));
+        }
+
+        out.push_str("});\n");
+    }
+
+    out
+}
+
+/// The models a zod schema refers to, `z.array(UserSchema)` uses `User`.
+fn schema_names(zod: &str) -> Vec<String> {
+    let mut names = zod
+        .split(|char: char| !char.is_alphanumeric() && char != '_')
+        .filter_map(|word| word.strip_suffix("Schema"))
+        .filter(|name| !name.is_empty())
+        .map(|name| name.to_string())
+        .collect::<Vec<String>>();
+
+    names.dedup();
+    names
+}
+
+/// Replaces `word` where it is a whole identifier.
+fn replace_word(text: &str, word: &str, replacement: &str) -> String {
+    let mut out = String::new();
+    let mut current = String::new();
+
+    for char in text.chars().chain(std::iter::once(' ')) {
+        if char.is_alphanumeric() || char == '_' {
+            current.push(char);
+            continue;
+        }
+
+        if current == word {
+            out.push_str(replacement);
+        } else {
+            out.push_str(&current);
+        }
+
+        current.clear();
+        out.push(char);
+    }
+
+    out.pop();
+    out
+}
+
+fn references(model: &Model, other: &Model) -> bool {
+    model.properties.iter().any(|property| {
+        property
+            .property_type
+            .split(|char: char| !char.is_alphanumeric() && char != '_')
+            .any(|word| word == other.name)
+    })
+}
+
+/// Models sorted so each comes after the models its properties use, models
+/// that refer to each other keep their source order.
+fn dependency_order(models: &[Model]) -> Vec<&Model> {
+    let mut ordered: Vec<&Model> = vec![];
+    let mut remaining = models.iter().collect::<Vec<&Model>>();
+
+    while !remaining.is_empty() {
+        let next = remaining
+            .iter()
+            .position(|model| {
+                !remaining
+                    .iter()
+                    .any(|other| other.name != model.name && references(model, other))
+            })
+            .unwrap_or(0);
+
+        ordered.push(remaining.remove(next));
+    }
+
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::generate_models;
+    use crate::{analyze::tests::analyze_file, TypeMap};
+
+    #[test]
+    fn generate_models_from_dtos() {
+        let data = analyze_file("./tests/mocks/models/UserTasks.cs");
+        let names = data
+            .models
+            .iter()
+            .map(|model| model.name.as_str())
+            .collect::<Vec<&str>>();
+
+        assert_eq!(
+            names,
+            vec!["AdminName", "UserTaskToAdd", "UserTaskDetails", "Comment"]
+        );
+        assert_eq!(data.models[1].properties.len(), 6);
+        assert_eq!(
+            data.models[2].properties[3].property_type,
+            "Dictionary<string, int>"
+        );
+
+        let output = generate_models(&data.models, &TypeMap::default());
+
+        assert!(output.contains(concat!(
+            "export interface UserTaskToAdd {\n",
+            "  UserTaskOid: string;\n",
+            "  UserOid: string;\n",
+            "  TaskName: string;\n",
+            "  OrderNumber: number | null;\n",
+            "  DueDate: Date | null;\n",
+            "  Tags: string[];\n",
+            "}\n"
+        )));
+        assert!(output.contains(concat!(
+            "export const UserTaskToAddSchema: z.ZodType<UserTaskToAdd> = z.object({\n",
+            "  UserTaskOid: z.string().uuid(),\n",
+            "  UserOid: z.string().uuid(),\n",
+            "  TaskName: z.string(),\n",
+            "  OrderNumber: z.number().int().nullable(),\n",
+            "  DueDate: z.coerce.date().nullable(),\n",
+            "  Tags: z.array(z.string()),\n",
+            "});\n"
+        )));
+        assert!(
+            output.contains("  SubTasks: UserTaskToAdd[];\n  Counts: Record<string, number>;\n")
+        );
+        assert!(output.contains("  SubTasks: z.array(UserTaskToAddSchema),\n"));
+        assert!(
+            output.find("export const UserTaskToAddSchema").unwrap()
+                < output.find("export const UserTaskDetailsSchema").unwrap()
+        );
+    }
+
+    #[test]
+    fn generate_models_with_cycles() {
+        let data = analyze_file("./tests/mocks/models/Graph.cs");
+        let output = generate_models(&data.models, &TypeMap::default());
+
+        assert!(output.contains("  Tasks: z.lazy(() => z.array(UserTaskSchema)),\n"));
+        assert!(output.contains("  User: UserSchema,\n"));
+        assert!(output.contains("  Parent: z.lazy(() => NodeSchema.nullable()),\n"));
+        assert!(output.contains("  Children: z.lazy(() => z.array(NodeSchema)),\n"));
+        assert!(output.contains("  Status: unknown; // TODO: Status is not modeled\n"));
+        assert!(output.contains("  Status: z.unknown(), // TODO: Status is not modeled\n"));
+        assert!(!output.contains("StatusSchema"));
+    }
+}
@@ -0,0 +1,136 @@
+use super::{
+    controller_blocks, controllers, http_method, index_key, method_blocks, parameters,
+    procedure_name, router_name, write_body, write_header, write_helper_functions,
+    write_index_imports, write_prisma_import, write_type_import, INDENT,
+};
+use crate::{BlockDetails, Data, TypeMap};
+
+pub fn generate_router(data: &Data, types: &TypeMap) -> String {
+    let mut out = String::new();
+    let controllers = controllers(data);
+    let blocks = controller_blocks(&controllers);
+    let qualified = controllers.len() > 1;
+
+    write_header(&controllers, &mut out);
+
+    let mut imports = String::new();
+    write_type_import(&blocks, types, &mut imports);
+    write_prisma_import(&blocks, &mut imports);
+
+    if !imports.is_empty() {
+        out.push_str(&imports);
+        out.push('\n');
+    }
+
+    for (index, controller) in controllers.iter().enumerate() {
+        if index > 0 {
+            out.push('\n');
+        }
+
+        write_router(controller, types, qualified, &mut out);
+    }
+
+    out
+}
+
+fn write_router(data: &Data, types: &TypeMap, qualified: bool, out: &mut String) {
+    let blocks = data.blocks.clone().unwrap_or_default();
+
+    write_helper_functions(data, &blocks, types, qualified, out);
+
+    out.push_str(&format!(
+        "export const {} = {{\n",
+        router_name(&data.class_name)
+    ));
+
+    for method in method_blocks(&blocks)
+        .iter()
+        .filter(|block| http_method(block).is_some())
+    {
+        if let Some(BlockDetails::MethodBlock {
+            name, variables, ..
+        }) = &method.details
+        {
+            out.push_str(&format!(
+                "{}{}: async ({}) => {{\n",
+                INDENT,
+                procedure_name(name),
+                parameters(variables, types)
+            ));
+            write_body(data, &blocks, types, method, 2, out);
+            out.push_str(&format!("{}}},\n", INDENT));
+        }
+    }
+
+    out.push_str("};\n");
+}
+
+pub fn generate_index(routers: &[(String, String)]) -> String {
+    let mut out = String::new();
+
+    write_index_imports(routers, &mut out);
+
+    if !routers.is_empty() {
+        out.push('\n');
+    }
+
+    out.push_str("export const appRouter = {\n");
+
+    for (_, router) in routers {
+        out.push_str(&format!("{}{}: {},\n", INDENT, index_key(router), router));
+    }
+
+    out.push_str("};\n");
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::generate_router;
+    use crate::{analyze::tests::analyze_file, TypeMap};
+
+    #[test]
+    fn generate_router_input_1() {
+        let data = analyze_file("./tests/mocks/input.cs");
+        let output = generate_router(&data, &TypeMap::default());
+
+        assert!(output.contains("export const testRouter = {"));
+        assert!(output.contains("  addAdmin: async (adminName: AdminName) => {"));
+        assert!(output.contains("  getClientTodoTasks: async (userOid: string) => {"));
+        assert!(output.contains("  completeTask: async (userTaskOid: string) => {"));
+        assert!(output.contains("async function updateUserTask(userTaskDetails: UserTaskToAdd) {"));
+        assert!(output.contains("async function addUserTask(userTaskDetails: UserTaskToAdd) {"));
+        assert!(output.contains("    const user: User = {"));
+        assert!(output.contains("    if (userTask != null) {"));
+        assert!(output.contains(
+            "    if (userTaskDetails.UserTaskOid === \"00000000-0000-0000-0000-000000000000\") {"
+        ));
+        assert!(output.contains("    else {"));
+        assert!(output.contains("    catch (ex) {"));
+        assert!(output.contains("    return await prisma.userTask.findMany({"));
+        assert!(output.contains("import { prisma } from \"../db\";"));
+        assert!(output.contains("import type { AdminName, UserTaskToAdd } from \"./schemas\";"));
+        assert!(!output.contains("TestController(IDbConnectionFactory"));
+
+        assert_eq!(output.matches(": async (").count(), 6);
+        assert_eq!(output.matches("async function ").count(), 2);
+
+        let code = output
+            .lines()
+            .filter(|line| !line.trim_start().starts_with("//"))
+            .collect::<String>();
+        assert_eq!(code.matches('{').count(), code.matches('}').count());
+    }
+
+    #[test]
+    fn generate_router_input_2() {
+        let data = analyze_file("./tests/mocks/input2.cs");
+        let output = generate_router(&data, &TypeMap::default());
+
+        assert!(output.contains("export const test2Router = {"));
+        assert!(output.contains("  getUploadSession: async (id: string) => {"));
+        assert!(output.contains("  fileChunk: async () => {"));
+        assert!(output.contains("async function isFileValidUpload(file: File) {"));
+    }
+}
@@ -0,0 +1,1333 @@
+use super::{expression::translate, lower_first, INDENT};
+use crate::{
+    Aggregate, AggregateKind, BlockDetails, Expression, JoinKind, OrderKey, Projection, QueryType,
+    ReturnData, SortDirection, Table, WhereClause,
+};
+
+const OPERATORS: [(&str, Option<&str>); 6] = [
+    ("==", None),
+    ("!=", Some("not")),
+    (">=", Some("gte")),
+    ("<=", Some("lte")),
+    (">", Some("gt")),
+    ("<", Some("lt")),
+];
+
+/// Turns the details of a select block into a Prisma client call, e.g.
+/// `prisma.userTask.findMany({ where: { UserOid: userOid } })`.
+pub fn select_query(details: &BlockDetails) -> Option<String> {
+    let BlockDetails::SelectBlock {
+        query_type,
+        tables,
+        where_clauses,
+        return_data,
+        order_by,
+        skip,
+        take,
+        aggregate,
+        projection,
+        ..
+    } = details
+    else {
+        return None;
+    };
+
+    let root = root_table(tables)?;
+    let find_method = match query_type {
+        QueryType::Many => "findMany",
+        QueryType::First => "findFirst",
+        QueryType::FirstOrThrow => "findFirstOrThrow",
+        QueryType::Unique => "findUnique",
+        QueryType::UniqueOrThrow => "findUniqueOrThrow",
+    };
+
+    let mut arguments = vec![];
+
+    let conditions = merge_entries(
+        where_clauses
+            .iter()
+            .flat_map(|clause| where_conditions(root, tables, clause))
+            .collect(),
+    );
+
+    if has_todo(&conditions) {
+        return None;
+    }
+
+    if !conditions.is_empty() {
+        arguments.push(format!("where: {}", object_literal(&conditions)));
+    }
+
+    let order_entries = order_by
+        .iter()
+        .map(|order_key| order_entry(root, tables, order_key))
+        .collect::<Vec<String>>();
+
+    match order_entries.as_slice() {
+        [] => (),
+        [entry] if !entry.starts_with("//") => arguments.push(format!("orderBy: {}", entry)),
+        entries if entries.iter().all(|entry| !entry.starts_with("//")) => {
+            arguments.push(format!("orderBy: [{}]", entries.join(", ")))
+        }
+        entries => arguments.extend(entries.iter().cloned()),
+    }
+
+    if let Some(skip) = skip {
+        arguments.push(format!("skip: {}", translate(skip)));
+    }
+
+    if let Some(take) = take {
+        arguments.push(format!("take: {}", translate(take)));
+    }
+
+    if let Some(aggregate) = aggregate {
+        return aggregate_query(root, tables, aggregate, arguments);
+    }
+
+    let relations = tables
+        .iter()
+        .filter(|table| table.shortcut != root.shortcut)
+        .map(|table| (relation_path(root, tables, table), table))
+        .collect::<Vec<(Vec<String>, &Table)>>();
+
+    if !return_data.is_empty() {
+        arguments.push(format!(
+            "select: {}",
+            select_object(
+                root,
+                &relations,
+                &[return_data.to_vec(), read_columns(tables, projection)].concat()
+            )
+        ));
+    } else if !relations.is_empty() {
+        arguments.push(format!(
+            "include: {}",
+            object_literal(&include_entries(&relations, &[]))
+        ));
+    }
+
+    let mut query = format!(
+        "prisma.{}.{}({})",
+        model_name(&root.name),
+        find_method,
+        object_literal(&arguments)
+    );
+
+    if needs_projection(root, return_data) || !unmapped_fields(projection).is_empty() {
+        query.push_str(&row_projection(
+            root,
+            &relations,
+            query_type,
+            return_data,
+            projection,
+        ));
+    }
+
+    Some(query)
+}
+
+/// `{ DueDate: "desc" }`, nested in the relations the column is read
+/// through.
+fn order_entry(root: &Table, tables: &[Table], order_key: &OrderKey) -> String {
+    let direction = match order_key.direction {
+        SortDirection::Ascending => "\"asc\"",
+        SortDirection::Descending => "\"desc\"",
+    };
+
+    let entry = match &order_key.key {
+        Expression::Column { shortcut, path } => {
+            let lambda_variable =
+                (!tables.iter().any(|table| table.shortcut == *shortcut)).then(|| shortcut.clone());
+            let filter = Filter {
+                root,
+                tables,
+                lambda_variable: &lambda_variable,
+            };
+
+            filter.field(shortcut, path, direction.to_string())
+        }
+        _ => None,
+    };
+
+    match entry {
+        Some(entry) => format!("{{ {} }}", entry),
+        None => format!("// TODO: orderBy {}", expression_text(&order_key.key)),
+    }
+}
+
+/// `count` for `Count()` and `Any()`, `aggregate` for the sums, minimums,
+/// maximums and averages of a column of the root table.
+fn aggregate_query(
+    root: &Table,
+    tables: &[Table],
+    aggregate: &Aggregate,
+    mut arguments: Vec<String>,
+) -> Option<String> {
+    let model = model_name(&root.name);
+
+    let field = match aggregate.kind {
+        AggregateKind::Count => {
+            return Some(format!(
+                "prisma.{}.count({})",
+                model,
+                object_literal(&arguments)
+            ));
+        }
+        AggregateKind::Any => {
+            return Some(format!(
+                "prisma.{}.count({}).then((count) => count > 0)",
+                model,
+                object_literal(&arguments)
+            ));
+        }
+        AggregateKind::Sum => "_sum",
+        AggregateKind::Max => "_max",
+        AggregateKind::Min => "_min",
+        AggregateKind::Average => "_avg",
+    };
+
+    let column = match &aggregate.selector {
+        Some(Expression::Column { shortcut, path })
+            if path.len() == 1
+                && (*shortcut == root.shortcut
+                    || !tables.iter().any(|table| table.shortcut == *shortcut)) =>
+        {
+            &path[0]
+        }
+        _ => return None,
+    };
+
+    arguments.push(format!("{}: {{ {}: true }}", field, column));
+
+    Some(format!(
+        "prisma.{}.aggregate({}).then((result) => result.{}.{})",
+        model,
+        object_literal(&arguments),
+        field,
+        column
+    ))
+}
+
+/// Turns an insert, delete or update into a Prisma client call, e.g.
+/// `prisma.user.create({ data: user })`. Deletes and updates find the row with
+/// the filters the entity was loaded with.
+pub fn write_query(details: &BlockDetails) -> Option<String> {
+    match details {
+        BlockDetails::InsertBlock {
+            table,
+            variable,
+            many,
+            ..
+        } => Some(format!(
+            "prisma.{}.{}({})",
+            model_name(table),
+            if *many { "createMany" } else { "create" },
+            object_literal(&[format!("data: {}", variable)])
+        )),
+        BlockDetails::DeleteBlock {
+            table,
+            where_clauses,
+            many,
+            ..
+        } => Some(format!(
+            "prisma.{}.{}({})",
+            model_name(table),
+            if *many { "deleteMany" } else { "delete" },
+            object_literal(&[format!(
+                "where: {}",
+                write_conditions(table, where_clauses)?
+            )])
+        )),
+        BlockDetails::UpdateBlock {
+            table,
+            where_clauses,
+            assignments,
+            ..
+        } => {
+            let fields = assignments
+                .iter()
+                .map(|assignment| {
+                    format!("{}: {}", assignment.property, translate(&assignment.value))
+                })
+                .collect::<Vec<String>>();
+
+            Some(format!(
+                "prisma.{}.update({})",
+                model_name(table),
+                object_literal(&[
+                    format!("where: {}", write_conditions(table, where_clauses)?),
+                    format!("data: {}", object_literal(&fields)),
+                ])
+            ))
+        }
+        _ => None,
+    }
+}
+
+fn write_conditions(table: &str, where_clauses: &[WhereClause]) -> Option<String> {
+    let root = Table {
+        name: table.to_string(),
+        shortcut: where_clauses
+            .iter()
+            .find_map(|clause| clause.shortcut.first().cloned())
+            .unwrap_or_default(),
+        joined_tables: vec![],
+        return_frequency: 0,
+        join: None,
+    };
+
+    let conditions = merge_entries(
+        where_clauses
+            .iter()
+            .flat_map(|clause| where_conditions(&root, std::slice::from_ref(&root), clause))
+            .collect(),
+    );
+
+    if conditions.is_empty() || has_todo(&conditions) {
+        return None;
+    }
+
+    Some(object_literal(&conditions))
+}
+
+/// A filter with a condition left as a comment would match every row, so the
+/// query is not written at all.
+fn has_todo(conditions: &[String]) -> bool {
+    conditions
+        .iter()
+        .any(|condition| condition.contains("// TODO"))
+}
+
+/// Prisma models are singular while `DbSet` properties are usually plural,
+/// `cx.UserTasks` becomes `prisma.userTask`.
+pub fn model_name(table_name: &str) -> String {
+    lower_first(&singular(table_name))
+}
+
+fn singular(name: &str) -> String {
+    if let Some(stem) = name.strip_suffix("ies") {
+        return format!("{}y", stem);
+    }
+
+    for suffix in ["sses", "uses", "xes", "ches", "shes"] {
+        if name.ends_with(suffix) {
+            return name[..name.len() - 2].to_string();
+        }
+    }
+
+    match name.strip_suffix('s') {
+        Some(stem) if !name.ends_with("ss") => stem.to_string(),
+        _ => name.to_string(),
+    }
+}
+
+/// The table most of the returned data comes from is the model the query
+/// starts on, every other table is reached through a relation.
+fn root_table(tables: &[Table]) -> Option<&Table> {
+    let max_frequency = tables.iter().map(|table| table.return_frequency).max()?;
+
+    tables
+        .iter()
+        .find(|table| table.return_frequency == max_frequency)
+}
+
+/// The table a joined table was joined to, read from the first key of its
+/// `join ... on ... equals ...`.
+fn parent_table<'a>(tables: &'a [Table], table: &Table) -> Option<&'a Table> {
+    let shortcut = &table.join.as_ref()?.keys.first()?.shortcut;
+
+    tables
+        .iter()
+        .find(|parent| parent.shortcut == *shortcut && parent.shortcut != table.shortcut)
+}
+
+/// The relations a table is reached through from the root, e.g.
+/// `["userTask", "taskStatus"]` for a status joined to the tasks of a user.
+/// Tables the root is not joined to are treated as a relation of the root.
+fn relation_path(root: &Table, tables: &[Table], table: &Table) -> Vec<String> {
+    let mut path = vec![];
+    let mut current = table;
+
+    while current.shortcut != root.shortcut && path.len() < tables.len() {
+        path.insert(0, model_name(&current.name));
+
+        match parent_table(tables, current) {
+            Some(parent) => current = parent,
+            None => break,
+        }
+    }
+
+    path
+}
+
+/// The relations one step further down `path`, in the order the query joins
+/// them.
+fn child_relations(relations: &[(Vec<String>, &Table)], path: &[String]) -> Vec<String> {
+    let mut children: Vec<String> = vec![];
+
+    for (relation_path, _) in relations {
+        if relation_path.len() > path.len() && relation_path.starts_with(path) {
+            let child = &relation_path[path.len()];
+
+            if !children.contains(child) {
+                children.push(child.clone());
+            }
+        }
+    }
+
+    children
+}
+
+/// `user: true`, or `user: { include: { ... } }` when more tables are joined
+/// through the relation.
+fn include_entries(relations: &[(Vec<String>, &Table)], path: &[String]) -> Vec<String> {
+    child_relations(relations, path)
+        .into_iter()
+        .map(|child| {
+            let child_path = [path, std::slice::from_ref(&child)].concat();
+            let nested = include_entries(relations, &child_path);
+
+            if nested.is_empty() {
+                format!("{}: true", child)
+            } else {
+                format!("{}: {{ include: {} }}", child, object_literal(&nested))
+            }
+        })
+        .collect()
+}
+
+fn where_conditions(root: &Table, tables: &[Table], clause: &WhereClause) -> Vec<String> {
+    if let Some(expression) = &clause.expression {
+        let filter = Filter {
+            root,
+            tables,
+            lambda_variable: &clause.lambda_varible,
+        };
+
+        return filter.entries_or_todo(expression);
+    }
+
+    let value = clause.value.trim();
+    let value = value.strip_prefix("where ").unwrap_or(value);
+    let value = value.strip_prefix("&& ").unwrap_or(value);
+
+    value
+        .split("&&")
+        .map(|condition| condition.trim())
+        .filter(|condition| !condition.is_empty())
+        .map(|condition| {
+            where_condition(root, tables, &clause.lambda_varible, condition)
+                .unwrap_or_else(|| format!("// TODO: {}", condition))
+        })
+        .collect()
+}
+
+/// Turns a where clause expression into the entries of a Prisma `where`
+/// object, e.g. `{ Name: { contains: search } }` or `{ OR: [...] }`.
+struct Filter<'a> {
+    root: &'a Table,
+    tables: &'a [Table],
+    lambda_variable: &'a Option<String>,
+}
+
+impl Filter<'_> {
+    fn entries_or_todo(&self, expression: &Expression) -> Vec<String> {
+        self.entries(expression)
+            .unwrap_or_else(|| vec![format!("// TODO: {}", expression_text(expression))])
+    }
+
+    fn entries(&self, expression: &Expression) -> Option<Vec<String>> {
+        match expression {
+            Expression::Binary { operator, .. } if operator == "||" => {
+                let branches = or_branches(expression)
+                    .iter()
+                    .map(|branch| Some(object_literal(&self.entries(branch)?)))
+                    .collect::<Option<Vec<String>>>()?;
+
+                Some(vec![format!("OR: [{}]", branches.join(", "))])
+            }
+            Expression::Binary {
+                operator,
+                left,
+                right,
+            } if operator == "&&" => Some(merge_entries(
+                [self.entries_or_todo(left), self.entries_or_todo(right)].concat(),
+            )),
+            Expression::Binary {
+                operator,
+                left,
+                right,
+            } => self
+                .comparison(operator, left, right)
+                .map(|entry| vec![entry]),
+            Expression::Not { operand } => match operand.as_ref() {
+                Expression::Column { shortcut, path } => {
+                    Some(vec![self.field(shortcut, path, "false".to_string())?])
+                }
+                operand => Some(vec![format!(
+                    "NOT: {}",
+                    object_literal(&self.entries(operand)?)
+                )]),
+            },
+            Expression::Column { shortcut, path } => {
+                Some(vec![self.field(shortcut, path, "true".to_string())?])
+            }
+            Expression::Call {
+                target,
+                method,
+                arguments,
+            } => self
+                .call(target, method, arguments)
+                .map(|entry| vec![entry]),
+            _ => None,
+        }
+    }
+
+    /// `ut.Age >= 18` or `18 <= ut.Age`, compared to anything but another
+    /// column.
+    fn comparison(&self, operator: &str, left: &Expression, right: &Expression) -> Option<String> {
+        let (column, value, operator) = match (left, right) {
+            (Expression::Column { .. }, value) => (left, value, operator.to_string()),
+            (value, Expression::Column { .. }) => (right, value, flipped(operator)),
+            _ => return None,
+        };
+
+        let (shortcut, path) = match column {
+            Expression::Column { shortcut, path } => (shortcut, path),
+            _ => return None,
+        };
+
+        if matches!(value, Expression::Column { .. }) {
+            return None;
+        }
+
+        let (_, filter) = OPERATORS.iter().find(|(known, _)| *known == operator)?;
+        let value = translate(&expression_text(value));
+
+        let filter = match filter {
+            Some(filter) => format!("{{ {}: {} }}", filter, value),
+            None => value,
+        };
+
+        self.field(shortcut, path, filter)
+    }
+
+    /// `ut.Name.Contains(search)` and `ids.Contains(ut.Id)`.
+    fn call(&self, target: &Expression, method: &str, arguments: &[Expression]) -> Option<String> {
+        match (target, method, arguments) {
+            (Expression::Column { shortcut, path }, _, [argument])
+                if !matches!(argument, Expression::Column { .. }) =>
+            {
+                let filter = match method {
+                    "Contains" => "contains",
+                    "StartsWith" => "startsWith",
+                    "EndsWith" => "endsWith",
+                    _ => return None,
+                };
+
+                self.field(
+                    shortcut,
+                    path,
+                    format!(
+                        "{{ {}: {} }}",
+                        filter,
+                        translate(&expression_text(argument))
+                    ),
+                )
+            }
+            (
+                Expression::Parameter { .. } | Expression::Variable { .. },
+                "Contains",
+                [Expression::Column { shortcut, path }],
+            ) => self.field(
+                shortcut,
+                path,
+                format!("{{ in: {} }}", translate(&expression_text(target))),
+            ),
+            _ => None,
+        }
+    }
+
+    /// `Property: filter` for a column of the root table, nested in the
+    /// relations the column is reached through.
+    fn field(&self, shortcut: &str, path: &[String], filter: String) -> Option<String> {
+        let (property, relations) = path.split_last()?;
+
+        let table = if self.lambda_variable.as_deref() == Some(shortcut) {
+            None
+        } else {
+            Some(
+                self.tables
+                    .iter()
+                    .find(|table| table.shortcut == shortcut)?,
+            )
+        };
+
+        let mut entry = format!("{}: {}", property, filter);
+
+        for relation in relations.iter().rev() {
+            entry = format!("{}: {{ {} }}", lower_first(relation), entry);
+        }
+
+        if let Some(table) = table {
+            for relation in relation_path(self.root, self.tables, table).iter().rev() {
+                entry = format!("{}: {{ {} }}", relation, entry);
+            }
+        }
+
+        Some(entry)
+    }
+}
+
+/// The operands of `a || b || c`, which the analysis reads as `(a || b) || c`.
+fn or_branches(expression: &Expression) -> Vec<&Expression> {
+    match expression {
+        Expression::Binary {
+            operator,
+            left,
+            right,
+        } if operator == "||" => [or_branches(left), or_branches(right)].concat(),
+        expression => vec![expression],
+    }
+}
+
+/// The comparison seen from the other side, `18 <= ut.Age` is `ut.Age >= 18`.
+fn flipped(operator: &str) -> String {
+    match operator {
+        "<" => ">",
+        ">" => "<",
+        "<=" => ">=",
+        ">=" => "<=",
+        operator => operator,
+    }
+    .to_string()
+}
+
+/// The C# an expression was read from.
+fn expression_text(expression: &Expression) -> String {
+    match expression {
+        Expression::Binary {
+            operator,
+            left,
+            right,
+        } => format!(
+            "{} {} {}",
+            expression_text(left),
+            operator,
+            expression_text(right)
+        ),
+        Expression::Not { operand } => format!("!{}", expression_text(operand)),
+        Expression::Column {
+            shortcut: name,
+            path,
+        }
+        | Expression::Parameter { name, path }
+        | Expression::Variable { name, path } => {
+            [vec![name.clone()], path.clone()].concat().join(".")
+        }
+        Expression::Literal { value } => value.clone(),
+        Expression::Call {
+            target,
+            method,
+            arguments,
+        } => format!(
+            "{}.{}({})",
+            expression_text(target),
+            method,
+            arguments
+                .iter()
+                .map(expression_text)
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+        Expression::Raw { text } => text.clone(),
+    }
+}
+
+fn where_condition(
+    root: &Table,
+    tables: &[Table],
+    lambda_variable: &Option<String>,
+    condition: &str,
+) -> Option<String> {
+    let (operator, filter) = OPERATORS
+        .iter()
+        .find(|(operator, _)| condition.contains(operator))?;
+
+    let parts = condition.splitn(2, operator).collect::<Vec<&str>>();
+    let left = parts[0].trim();
+    let right = parts[1].trim();
+
+    let column_table = |operand: &str| -> Option<(Option<&Table>, String)> {
+        let (shortcut, property) = operand.split_once('.')?;
+
+        if lambda_variable.as_deref() == Some(shortcut) {
+            return Some((None, property.to_string()));
+        }
+
+        tables
+            .iter()
+            .find(|table| table.shortcut == shortcut)
+            .map(|table| (Some(table), property.to_string()))
+    };
+
+    let ((table, property), value) = match (column_table(left), column_table(right)) {
+        (Some(column), _) => (column, right),
+        (None, Some(column)) => (column, left),
+        _ => return None,
+    };
+
+    let value = translate(value);
+    let filter = match filter {
+        Some(filter) => format!("{{ {}: {} }}", filter, value),
+        None => value,
+    };
+
+    let mut entry = format!("{}: {}", property, filter);
+
+    if let Some(table) = table {
+        for relation in relation_path(root, tables, table).iter().rev() {
+            entry = format!("{}: {{ {} }}", relation, entry);
+        }
+    }
+
+    Some(entry)
+}
+
+fn select_object(
+    root: &Table,
+    relations: &[(Vec<String>, &Table)],
+    return_data: &[ReturnData],
+) -> String {
+    let mut fields = vec![];
+
+    for data in return_data
+        .iter()
+        .filter(|data| is_root_data(root, data) && !is_computed(data))
+    {
+        let field = format!("{}: true", data.value);
+
+        if !fields.contains(&field) {
+            fields.push(field);
+        }
+    }
+
+    fields.extend(relation_selects(relations, return_data, &[]));
+
+    object_literal(&fields)
+}
+
+/// `user: { select: { ... } }` for every relation below `path` that data is
+/// returned from, with the relations joined through it nested inside.
+fn relation_selects(
+    relations: &[(Vec<String>, &Table)],
+    return_data: &[ReturnData],
+    path: &[String],
+) -> Vec<String> {
+    let mut selects = vec![];
+
+    for child in child_relations(relations, path) {
+        let child_path = [path, std::slice::from_ref(&child)].concat();
+        let mut fields = vec![];
+
+        for (_, table) in relations
+            .iter()
+            .filter(|(relation_path, _)| *relation_path == child_path)
+        {
+            for data in return_data.iter().filter(|data| data.table == table.name) {
+                let field = format!("{}: true", data.value);
+
+                if !fields.contains(&field) {
+                    fields.push(field);
+                }
+            }
+        }
+
+        fields.extend(relation_selects(relations, return_data, &child_path));
+
+        if !fields.is_empty() {
+            selects.push(format!(
+                "{}: {}",
+                child,
+                object_literal(&[format!("select: {}", object_literal(&fields))])
+            ));
+        }
+    }
+
+    selects
+}
+
+fn is_left_join(table: &Table) -> bool {
+    table
+        .join
+        .as_ref()
+        .is_some_and(|join| join.kind == JoinKind::Left)
+}
+
+fn is_root_data(root: &Table, data: &ReturnData) -> bool {
+    data.table == root.name || data.table.is_empty()
+}
+
+/// Values that are not read from a column, like `DateTime.UtcNow`, are
+/// computed in the projection instead of selected.
+fn is_computed(data: &ReturnData) -> bool {
+    data.table.is_empty()
+        && !data
+            .value
+            .chars()
+            .all(|char| char.is_alphanumeric() || char == '_')
+}
+
+/// Prisma returns columns under their own names, so anything renamed or read
+/// from a relation has to be mapped back onto the original C# shape.
+fn needs_projection(root: &Table, return_data: &[ReturnData]) -> bool {
+    return_data
+        .iter()
+        .any(|data| !is_root_data(root, data) || data.property != data.value)
+}
+
+fn row_projection(
+    root: &Table,
+    relations: &[(Vec<String>, &Table)],
+    query_type: &QueryType,
+    return_data: &[ReturnData],
+    projection: &Option<Projection>,
+) -> String {
+    let mut fields = return_data
+        .iter()
+        .map(|data| {
+            if is_computed(data) {
+                let expression = row_expression(
+                    &data.value,
+                    &field_shortcuts(projection, &data.property),
+                    root,
+                    relations,
+                );
+
+                format!("{}: {}", data.property, translate(&expression))
+            } else if is_root_data(root, data) {
+                format!("{}: row.{}", data.property, data.value)
+            } else {
+                // a left join may not find a row
+                let (path, separator) =
+                    match relations.iter().find(|(_, table)| table.name == data.table) {
+                        Some((path, table)) if is_left_join(table) => (path.join("."), "?."),
+                        Some((path, _)) => (path.join("."), "."),
+                        None => (model_name(&data.table), "."),
+                    };
+
+                format!("{}: row.{}{}{}", data.property, path, separator, data.value)
+            }
+        })
+        .collect::<Vec<String>>();
+
+    fields.extend(
+        unmapped_fields(projection)
+            .into_iter()
+            .map(|name| format!("// TODO: {}", name)),
+    );
+
+    let fields = object_literal(&fields).replace('\n', &format!("\n{}", INDENT));
+
+    match query_type {
+        QueryType::Many => format!(
+            ".then((rows) =>\n{}rows.map((row) => ({}))\n)",
+            INDENT, fields
+        ),
+        _ => format!(".then((row) =>\n{}row && ({})\n)", INDENT, fields),
+    }
+}
+
+/// The fields of the selected object that are computed from columns, or
+/// read through a relation, with the columns they need selected.
+fn read_columns(tables: &[Table], projection: &Option<Projection>) -> Vec<ReturnData> {
+    let Some(Projection::Object { fields, .. }) = projection else {
+        return vec![];
+    };
+
+    let column = |shortcut: &String, property: &String| ReturnData {
+        table: tables
+            .iter()
+            .find(|table| table.shortcut == *shortcut)
+            .map(|table| table.name.clone())
+            .unwrap_or_default(),
+        property: property.clone(),
+        value: property.clone(),
+    };
+
+    fields
+        .iter()
+        .flat_map(|field| match &field.value {
+            Projection::Computed {
+                shortcuts,
+                properties,
+                ..
+            } => shortcuts
+                .iter()
+                .zip(properties)
+                .map(|(shortcut, property)| column(shortcut, property))
+                .collect(),
+            Projection::Column { shortcut, path } if path.len() > 1 => {
+                vec![column(shortcut, &path[0])]
+            }
+            _ => vec![],
+        })
+        .collect()
+}
+
+/// The shortcuts the value of a field reads columns from.
+fn field_shortcuts(projection: &Option<Projection>, name: &str) -> Vec<String> {
+    let Some(Projection::Object { fields, .. }) = projection else {
+        return vec![];
+    };
+
+    match fields
+        .iter()
+        .find(|field| field.name == name)
+        .map(|field| &field.value)
+    {
+        Some(Projection::Computed { shortcuts, .. }) => shortcuts.clone(),
+        Some(Projection::Column { shortcut, .. }) => vec![shortcut.clone()],
+        _ => vec![],
+    }
+}
+
+/// Nested objects, lists and whole rows are not mapped from the Prisma rows
+/// yet.
+fn unmapped_fields(projection: &Option<Projection>) -> Vec<String> {
+    let Some(Projection::Object { fields, .. }) = projection else {
+        return vec![];
+    };
+
+    fields
+        .iter()
+        .filter(|field| {
+            matches!(
+                field.value,
+                Projection::Object { .. } | Projection::Collection { .. } | Projection::Row { .. }
+            )
+        })
+        .map(|field| field.name.clone())
+        .collect()
+}
+
+/// Points the columns a C# expression reads at the row Prisma returns,
+/// `ut.Name` becomes `row.Name` and `u.Email` becomes `row.user.Email`.
+/// Strings are left as they are.
+fn row_expression(
+    expression: &str,
+    shortcuts: &[String],
+    root: &Table,
+    relations: &[(Vec<String>, &Table)],
+) -> String {
+    let chars = expression.chars().collect::<Vec<char>>();
+    let is_word = |char: char| char.is_alphanumeric() || char == '_';
+
+    let mut result = String::new();
+    let mut in_string = false;
+    let mut index = 0;
+
+    while index < chars.len() {
+        let char = chars[index];
+        let previous = index.checked_sub(1).map(|index| chars[index]);
+
+        if char == '"' && previous != Some('\\') {
+            in_string = !in_string;
+        }
+
+        if in_string || !is_word(char) || previous.is_some_and(|c| is_word(c) || c == '.') {
+            result.push(char);
+            index += 1;
+            continue;
+        }
+
+        let end = index + chars[index..].iter().take_while(|c| is_word(**c)).count();
+        let word = chars[index..end].iter().collect::<String>();
+
+        if chars.get(end) == Some(&'.') && shortcuts.contains(&word) {
+            match relations
+                .iter()
+                .find(|(_, table)| table.shortcut == word && table.shortcut != root.shortcut)
+            {
+                Some((path, _)) => result.push_str(&format!("row.{}", path.join("."))),
+                None => result.push_str("row"),
+            }
+        } else {
+            result.push_str(&word);
+        }
+
+        index = end;
+    }
+
+    result
+}
+
+/// Entries that filter the same field twice, like the bounds of
+/// `min < u.Age && u.Age <= 10`, are merged into `Age: { gt: min, lte: 10 }`.
+/// When their filters overlap, or the field is not filtered with an object,
+/// they are kept apart in `AND: [...]`, an object literal keeps only the last
+/// of two equal keys.
+fn merge_entries(entries: Vec<String>) -> Vec<String> {
+    let key = |entry: &str| {
+        entry
+            .split_once(": ")
+            .filter(|_| !entry.starts_with("//"))
+            .map(|(key, _)| key.to_string())
+    };
+
+    let mut merged: Vec<String> = vec![];
+    let mut groups: Vec<(String, Vec<String>)> = vec![];
+
+    for entry in entries {
+        match key(&entry) {
+            Some(key) => match groups.iter_mut().find(|(other, _)| *other == key) {
+                Some((_, group)) => group.push(entry),
+                None => {
+                    groups.push((key.clone(), vec![entry]));
+                    merged.push(key);
+                }
+            },
+            None => merged.push(entry),
+        }
+    }
+
+    merged
+        .into_iter()
+        .map(|entry| match groups.iter().find(|(key, _)| *key == entry) {
+            Some((_, group)) if group.len() == 1 => group[0].clone(),
+            Some((key, group)) => merge_filters(key, group).unwrap_or_else(|| {
+                let objects = group
+                    .iter()
+                    .map(|entry| format!("{{ {} }}", entry))
+                    .collect::<Vec<String>>();
+
+                format!("AND: [{}]", objects.join(", "))
+            }),
+            None => entry,
+        })
+        .collect()
+}
+
+/// `Age: { gt: min }` and `Age: { lte: 10 }` as `Age: { gt: min, lte: 10 }`,
+/// none when a filter is repeated or a value is not a filter object.
+fn merge_filters(key: &str, group: &[String]) -> Option<String> {
+    let mut filters: Vec<String> = vec![];
+
+    for entry in group {
+        let value = entry[key.len() + 2..].trim();
+        let inner = value.strip_prefix("{ ")?.strip_suffix(" }")?;
+
+        for filter in split_top_level(inner) {
+            let (name, _) = filter.split_once(": ")?;
+
+            if name.contains(['{', '(', '"'])
+                || filters
+                    .iter()
+                    .any(|other| other.split_once(": ").map(|(other, _)| other) == Some(name))
+            {
+                return None;
+            }
+
+            filters.push(filter);
+        }
+    }
+
+    Some(format!("{}: {{ {} }}", key, filters.join(", ")))
+}
+
+/// Splits the inside of an object literal on the commas between its entries.
+fn split_top_level(text: &str) -> Vec<String> {
+    let mut parts = vec![];
+    let mut current = String::new();
+    let mut depth = 0;
+    let mut quote = None;
+
+    for char in text.chars() {
+        match (quote, char) {
+            (Some(open), char) if char == open => quote = None,
+            (Some(_), _) => (),
+            (None, '"' | '\'' | '`') => quote = Some(char),
+            (None, '(' | '[' | '{') => depth += 1,
+            (None, ')' | ']' | '}') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                parts.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => (),
+        }
+
+        current.push(char);
+    }
+
+    parts.push(current.trim().to_string());
+    parts
+}
+
+/// Writes `{ a: 1 }` on one line when there is a single entry and spreads the
+/// entries over multiple lines otherwise, entries starting with `//` are
+/// written as comments.
+fn object_literal(entries: &[String]) -> String {
+    if entries.is_empty() {
+        return "{}".to_string();
+    }
+
+    if entries.len() == 1 && !entries[0].starts_with("//") && !entries[0].contains('\n') {
+        return format!("{{ {} }}", entries[0]);
+    }
+
+    let mut result = "{\n".to_string();
+
+    for entry in entries {
+        let entry = entry.replace('\n', &format!("\n{}", INDENT));
+        let separator = if entry.starts_with("//") { "" } else { "," };
+
+        result.push_str(&format!("{}{}{}\n", INDENT, entry, separator));
+    }
+
+    result.push('}');
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{model_name, select_query, write_query};
+    use crate::analyze::tests::analyze_file;
+    use crate::{BlockDetails, BlockType};
+
+    fn select_details(path: &str) -> Vec<BlockDetails> {
+        analyze_file(path)
+            .blocks
+            .unwrap()
+            .into_iter()
+            .filter(|block| block.block_type == BlockType::Select)
+            .filter_map(|block| block.details)
+            .collect()
+    }
+
+    #[test]
+    fn model_names_are_singular() {
+        assert_eq!(model_name("UserTasks"), "userTask");
+        assert_eq!(model_name("Users"), "user");
+        assert_eq!(model_name("TaskStatuses"), "taskStatus");
+        assert_eq!(model_name("Categories"), "category");
+        assert_eq!(model_name("Boxes"), "box");
+        assert_eq!(model_name("Address"), "address");
+    }
+
+    #[test]
+    fn select_queries_input_1() {
+        let selects = select_details("./tests/mocks/input.cs");
+
+        assert_eq!(
+            select_query(&selects[0]).unwrap(),
+            [
+                "prisma.userTask.findMany({",
+                "  where: { UserOid: userOid },",
+                "  orderBy: { OrderNumber: \"asc\" },",
+                "  select: {",
+                "    UserTaskOid: true,",
+                "    Name: true,",
+                "    TaskStatusId: true,",
+                "    StartDate: true,",
+                "    OrderNumber: true,",
+                "    CompleteDate: true,",
+                "    taskStatus: { select: { Name: true } },",
+                "    user: { select: { UserOid: true } },",
+                "  },",
+                "}).then((rows) =>",
+                "  rows.map((row) => ({",
+                "    UserTaskOid: row.UserTaskOid,",
+                "    Name: row.Name,",
+                "    TaskStatusId: row.TaskStatusId,",
+                "    StartDate: row.StartDate,",
+                "    OrderNumber: row.OrderNumber,",
+                "    UserOid: row.user.UserOid,",
+                concat!(
+                    "    CompleteDate: row.CompleteDate != null",
+                    " ? row.CompleteDate.ToString(\"yyyy-MM-dd HH:mm:ss\") /* TODO: translate */",
+                    " : \"\","
+                ),
+                "    TaskStatus: row.taskStatus.Name,",
+                "  }))",
+                ")",
+            ]
+            .join("\n")
+        );
+
+        assert!(select_query(&selects[1])
+            .unwrap()
+            .starts_with("prisma.userTask.findUnique({\n  where: { UserTaskOid: userTaskOid },"));
+
+        assert_eq!(
+            select_query(&selects[2]).unwrap(),
+            "prisma.userTask.findUnique({ where: { UserTaskOid: userTaskOid } })"
+        );
+
+        assert_eq!(
+            select_query(&selects[4]).unwrap(),
+            "prisma.userTask.findUnique({ where: { UserTaskOid: userTaskDetails.UserTaskOid } })"
+        );
+    }
+
+    #[test]
+    fn write_queries_input_1() {
+        let writes = analyze_file("./tests/mocks/input.cs")
+            .blocks
+            .unwrap()
+            .into_iter()
+            .filter(|block| block.block_type == BlockType::Write)
+            .filter_map(|block| block.details)
+            .collect::<Vec<BlockDetails>>();
+
+        assert_eq!(
+            write_query(&writes[0]).unwrap(),
+            "prisma.user.create({ data: user })"
+        );
+        assert_eq!(
+            write_query(&writes[3]).unwrap(),
+            [
+                "prisma.userTask.update({",
+                "  where: { UserTaskOid: userTaskOid },",
+                "  data: {",
+                "    CompleteDate: new Date(),",
+                "    TaskStatusId: TaskStatuses.Complete,",
+                "  },",
+                "})",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            write_query(&writes[5]).unwrap(),
+            "prisma.userTask.delete({ where: { UserTaskOid: userTaskOid } })"
+        );
+        assert_eq!(write_query(&writes[6]), None);
+    }
+
+    #[test]
+    fn untranslated_filters_write_no_query() {
+        let analysis = crate::analyze_source(concat!(
+            "public class ItemsController : ControllerBase\n",
+            "{\n",
+            "    [HttpDelete]\n",
+            "    public void Prune(string tag)\n",
+            "    {\n",
+            "        using (var cx = _dbFactory.CreateContext())\n",
+            "        {\n",
+            "            var items = cx.Items.Where(i => IsStale(i.Name, tag)).ToList();\n",
+            "            cx.Items.RemoveRange(items);\n",
+            "            cx.SaveChanges();\n",
+            "        }\n",
+            "    }\n",
+            "}\n",
+        ));
+        let details = analysis
+            .blocks()
+            .iter()
+            .filter_map(|block| block.details.clone())
+            .collect::<Vec<BlockDetails>>();
+
+        let select = details
+            .iter()
+            .find(|details| matches!(details, BlockDetails::SelectBlock { .. }))
+            .unwrap();
+        let delete = details
+            .iter()
+            .find(|details| matches!(details, BlockDetails::DeleteBlock { many: true, .. }))
+            .unwrap();
+
+        assert_eq!(select_query(select), None);
+        assert_eq!(write_query(delete), None);
+    }
+
+    #[test]
+    fn where_filters_input_4() {
+        let selects = select_details("./tests/mocks/input4.cs");
+
+        assert_eq!(
+            select_query(&selects[1]).unwrap(),
+            [
+                "prisma.userTask.findMany({",
+                "  where: {",
+                "    OR: [{ Name: { contains: search } }, { Description: { startsWith: search } }],",
+                "    OrderNumber: { gte: minOrder },",
+                "    CompleteDate: null,",
+                "  },",
+                "})",
+            ]
+            .join("\n")
+        );
+        assert!(select_query(&selects[2]).unwrap().contains(
+            "    UserOid: { in: userOids },\n    IsDeleted: false,\n    TaskStatusId: { not: 3 },\n"
+        ));
+        assert!(select_query(&selects[0]).unwrap().contains(
+            "    OR: [{ DueDate: { lt: now } }, { user: { Email: { endsWith: \"@example.com\" } } }],\n"
+        ));
+        assert_eq!(
+            select_query(&selects[3]).unwrap(),
+            [
+                "prisma.userTask.findMany({",
+                "  where: {",
+                "    OrderNumber: { gt: min, lte: 10 },",
+                "    AND: [{ Name: { not: null } }, { Name: { not: \"\" } }],",
+                "  },",
+                "})",
+            ]
+            .join("\n")
+        );
+        assert_eq!(select_query(&selects[4]), None);
+    }
+
+    #[test]
+    fn joined_relations_input_5() {
+        let selects = select_details("./tests/mocks/input5.cs");
+        let query = select_query(&selects[0]).unwrap();
+
+        assert!(query.contains("    user: { company: { IsActive: true } },\n"));
+        assert!(query.contains(concat!(
+            "    taskStatus: { select: { Name: true } },\n",
+            "    user: {\n",
+            "      select: {\n",
+            "        Email: true,\n",
+            "        company: { select: { Name: true } },\n",
+            "      },\n",
+            "    },\n",
+        )));
+        assert!(query.contains("    Company: row.user.company.Name,\n"));
+        assert!(query.contains("    Status: row.taskStatus?.Name,\n"));
+    }
+
+    #[test]
+    fn ordering_paging_and_aggregates_input_5() {
+        let selects = select_details("./tests/mocks/input5.cs");
+
+        assert!(select_query(&selects[1]).unwrap().starts_with(concat!(
+            "prisma.userTask.findFirstOrThrow({\n",
+            "  where: { UserOid: userOid },\n",
+            "  orderBy: [{ DueDate: \"desc\" }, { Name: \"asc\" }],\n",
+        )));
+        assert!(select_query(&selects[2]).unwrap().contains(concat!(
+            "  orderBy: [{ DueDate: \"desc\" }, { Name: \"asc\" }],\n",
+            "  skip: page * 20,\n",
+            "  take: 20,\n",
+        )));
+        assert!(select_query(&selects[3])
+            .unwrap()
+            .starts_with("prisma.userTask.count({\n"));
+        assert_eq!(
+            select_query(&selects[4]).unwrap(),
+            "prisma.userTask.count({ where: { UserOid: userOid } }).then((count) => count > 0)"
+        );
+        assert_eq!(
+            select_query(&selects[5]).unwrap(),
+            [
+                "prisma.userTask.aggregate({",
+                "  where: { UserOid: userOid },",
+                "  _sum: { Hours: true },",
+                "}).then((result) => result._sum.Hours)",
+            ]
+            .join("\n")
+        );
+    }
+}
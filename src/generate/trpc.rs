@@ -0,0 +1,180 @@
+use super::zod::{input_schema, write_schema_import};
+use super::{
+    controller_blocks, controllers, http_method, index_key, input_variables, method_blocks,
+    procedure_name, router_name, write_body, write_header, write_helper_functions,
+    write_index_imports, write_prisma_import, write_type_import, INDENT,
+};
+use crate::{BlockDetails, Data, HttpType, TypeMap};
+
+pub fn generate_router(data: &Data, types: &TypeMap) -> String {
+    let mut out = String::new();
+    let controllers = controllers(data);
+    let blocks = controller_blocks(&controllers);
+
+    write_header(&controllers, &mut out);
+    out.push_str("import { z } from \"zod\";\n");
+    out.push_str("import { router, publicProcedure } from \"../trpc\";\n");
+    write_schema_import(&input_variables(&blocks), types, &mut out);
+    write_type_import(&blocks, types, &mut out);
+    write_prisma_import(&blocks, &mut out);
+
+    let qualified = controllers.len() > 1;
+
+    for controller in &controllers {
+        out.push('\n');
+        write_router(controller, types, qualified, &mut out);
+    }
+
+    out
+}
+
+fn write_router(data: &Data, types: &TypeMap, qualified: bool, out: &mut String) {
+    let blocks = data.blocks.clone().unwrap_or_default();
+
+    write_helper_functions(data, &blocks, types, qualified, out);
+
+    out.push_str(&format!(
+        "export const {} = router({{\n",
+        router_name(&data.class_name)
+    ));
+
+    for method in method_blocks(&blocks) {
+        let http_method = match http_method(method) {
+            Some(http_method) => http_method,
+            None => continue,
+        };
+
+        if let Some(BlockDetails::MethodBlock {
+            name, variables, ..
+        }) = &method.details
+        {
+            out.push_str(&format!(
+                "{}{}: publicProcedure\n",
+                INDENT,
+                procedure_name(name)
+            ));
+
+            if !variables.is_empty() {
+                out.push_str(&format!(
+                    "{}.input({})\n",
+                    INDENT.repeat(2),
+                    input_schema(variables, types)
+                ));
+            }
+
+            let procedure_type = match http_method {
+                HttpType::HttpGet => "query",
+                _ => "mutation",
+            };
+
+            let arguments = if variables.is_empty() {
+                "async () => {".to_string()
+            } else {
+                "async ({ input }) => {".to_string()
+            };
+
+            out.push_str(&format!(
+                "{}.{}({}\n",
+                INDENT.repeat(2),
+                procedure_type,
+                arguments
+            ));
+
+            if !variables.is_empty() {
+                let names = variables
+                    .iter()
+                    .map(|variable| variable.name.clone())
+                    .collect::<Vec<String>>()
+                    .join(", ");
+
+                out.push_str(&format!(
+                    "{}const {{ {} }} = input;\n",
+                    INDENT.repeat(3),
+                    names
+                ));
+            }
+
+            write_body(data, &blocks, types, method, 3, out);
+            out.push_str(&format!("{}}}),\n", INDENT.repeat(2)));
+        }
+    }
+
+    out.push_str("});\n");
+}
+
+pub fn generate_index(routers: &[(String, String)]) -> String {
+    let mut out = String::new();
+
+    out.push_str("import { router } from \"../trpc\";\n");
+    write_index_imports(routers, &mut out);
+    out.push_str("\nexport const appRouter = router({\n");
+
+    for (_, router) in routers {
+        out.push_str(&format!("{}{}: {},\n", INDENT, index_key(router), router));
+    }
+
+    out.push_str("});\n\nexport type AppRouter = typeof appRouter;\n");
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::generate_router;
+    use crate::{analyze::tests::analyze_file, TypeMap};
+
+    #[test]
+    fn generate_trpc_router_input_1() {
+        let data = analyze_file("./tests/mocks/input.cs");
+        let output = generate_router(&data, &TypeMap::default());
+
+        assert!(output.contains("import { router, publicProcedure } from \"../trpc\";"));
+        assert!(
+            output.contains("import { AdminNameSchema, UserTaskToAddSchema } from \"./schemas\";")
+        );
+        assert!(output.contains("export const testRouter = router({"));
+        assert!(output.contains(
+            "  getClientTodoTasks: publicProcedure\n    .input(z.object({ userOid: z.string().uuid() }))\n    .query(async ({ input }) => {\n      const { userOid } = input;\n"
+        ));
+        assert!(output.contains(
+            "  addAdmin: publicProcedure\n    .input(z.object({ adminName: AdminNameSchema }))\n    .mutation(async ({ input }) => {\n"
+        ));
+        assert!(output.contains("  completeTask: publicProcedure\n"));
+        assert!(output.contains("  deleteUserTask: publicProcedure\n"));
+        assert!(output.contains("async function addUserTask(userTaskDetails: UserTaskToAdd) {"));
+
+        assert_eq!(output.matches(".query(").count(), 2);
+        assert_eq!(output.matches(".mutation(").count(), 4);
+        assert!(output.ends_with("    }),\n});\n"));
+    }
+
+    #[test]
+    fn generate_trpc_router_input_2() {
+        let data = analyze_file("./tests/mocks/input2.cs");
+        let output = generate_router(&data, &TypeMap::default());
+
+        assert!(output.contains("export const test2Router = router({"));
+        assert!(output.contains("  fileChunk: publicProcedure\n    .mutation(async () => {\n"));
+        assert!(output.contains(
+            "  uploadFile: publicProcedure\n    .input(z.object({ _: z.array(z.custom<File>()) }))\n"
+        ));
+    }
+
+    #[test]
+    fn generate_trpc_router_per_controller() {
+        let data = analyze_file("./tests/mocks/shop/ShopController.cs");
+        let output = generate_router(&data, &TypeMap::default());
+
+        assert!(output.starts_with(
+            "// Generated from UsersController, OrdersController by controller_to_router_rs\n"
+        ));
+        assert!(output.contains("export const usersRouter = router({\n  get: publicProcedure\n"));
+        assert!(output.contains("export const ordersRouter = router({\n  get: publicProcedure\n"));
+        assert!(output.contains("// TODO: return \"user\";"));
+        assert!(output.contains("// TODO: return \"order\";"));
+        assert!(!output.contains("run"));
+
+        assert_eq!(output.matches("export const ").count(), 2);
+        assert_eq!(output.matches("import ").count(), 2);
+    }
+}
@@ -0,0 +1,101 @@
+use super::expression::{translate, TODO_MARKER};
+use crate::types::zod_type;
+use crate::{TypeMap, Variable};
+
+/// `z.object({ ... })` with a field per parameter. A parameter with a default
+/// value may be left out, `int page = 1` is `z.number().int().default(1)`.
+pub fn input_schema(variables: &[Variable], types: &TypeMap) -> String {
+    let fields = variables
+        .iter()
+        .map(|variable| {
+            let schema = zod_type(types, &variable.variable_type);
+
+            let schema = match variable.default_value.as_deref().map(translate) {
+                Some(value)
+                    if value.contains(TODO_MARKER)
+                        || value == "default"
+                        || value.starts_with("default(") =>
+                {
+                    format!("{}.optional()", schema)
+                }
+                Some(value) => format!("{}.default({})", schema, value),
+                None => schema,
+            };
+
+            format!("{}: {}", variable.name, schema)
+        })
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    format!("z.object({{ {} }})", fields)
+}
+
+/// The DTO schemas the parameters refer to, sorted and without duplicates.
+pub fn schema_references(variables: &[&Variable], types: &TypeMap) -> Vec<String> {
+    let mut references = variables
+        .iter()
+        .flat_map(|variable| {
+            zod_type(types, &variable.variable_type)
+                .split(|char: char| !char.is_alphanumeric() && char != '_')
+                .filter(|word| word.ends_with("Schema") && *word != "Schema")
+                .map(|word| word.to_string())
+                .collect::<Vec<String>>()
+        })
+        .collect::<Vec<String>>();
+
+    references.sort();
+    references.dedup();
+
+    references
+}
+
+pub fn write_schema_import(variables: &[&Variable], types: &TypeMap, out: &mut String) {
+    let references = schema_references(variables, types);
+
+    if !references.is_empty() {
+        out.push_str(&format!(
+            "import {{ {} }} from \"./schemas\";\n",
+            references.join(", ")
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::schema_references;
+    use crate::{TypeMap, Variable};
+
+    #[test]
+    fn collects_dto_references() {
+        let variables = [
+            Variable {
+                name: "tasks".to_string(),
+                variable_type: "List<UserTaskToAdd>".to_string(),
+                default_value: None,
+            },
+            Variable {
+                name: "admin".to_string(),
+                variable_type: "AdminName?".to_string(),
+                default_value: None,
+            },
+            Variable {
+                name: "task".to_string(),
+                variable_type: "UserTaskToAdd".to_string(),
+                default_value: None,
+            },
+            Variable {
+                name: "id".to_string(),
+                variable_type: "Guid".to_string(),
+                default_value: None,
+            },
+        ];
+
+        assert_eq!(
+            schema_references(
+                &variables.iter().collect::<Vec<&Variable>>(),
+                &TypeMap::default()
+            ),
+            vec!["AdminNameSchema", "UserTaskToAddSchema"]
+        );
+    }
+}
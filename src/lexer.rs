@@ -0,0 +1,528 @@
+use crate::{Span, Token, TokenKind};
+
+const KEYWORDS: &[&str] = &[
+    "abstract",
+    "as",
+    "base",
+    "bool",
+    "break",
+    "byte",
+    "case",
+    "catch",
+    "char",
+    "checked",
+    "class",
+    "const",
+    "continue",
+    "decimal",
+    "default",
+    "delegate",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "event",
+    "explicit",
+    "extern",
+    "false",
+    "finally",
+    "fixed",
+    "float",
+    "for",
+    "foreach",
+    "goto",
+    "if",
+    "implicit",
+    "in",
+    "int",
+    "interface",
+    "internal",
+    "is",
+    "lock",
+    "long",
+    "namespace",
+    "new",
+    "null",
+    "object",
+    "operator",
+    "out",
+    "override",
+    "params",
+    "private",
+    "protected",
+    "public",
+    "readonly",
+    "ref",
+    "return",
+    "sbyte",
+    "sealed",
+    "short",
+    "sizeof",
+    "stackalloc",
+    "static",
+    "string",
+    "struct",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "uint",
+    "ulong",
+    "unchecked",
+    "unsafe",
+    "ushort",
+    "using",
+    "virtual",
+    "void",
+    "volatile",
+    "while",
+    "var",
+    "from",
+    "join",
+    "where",
+    "select",
+    "on",
+    "equals",
+    "into",
+    "orderby",
+    "ascending",
+    "descending",
+    "group",
+    "by",
+    "let",
+    "record",
+    "async",
+    "await",
+    "get",
+    "set",
+    "init",
+];
+
+const OPERATORS: &[&str] = &[
+    "??=", "=>", "==", "!=", ">=", "<=", "&&", "||", "??", "?.", "++", "--", "+=", "-=", "*=",
+    "/=", "::", "->",
+];
+
+struct Cursor<'a> {
+    chars: Vec<char>,
+    lines: &'a [String],
+    index: usize,
+    line: u32,
+    column: u32,
+}
+
+impl Cursor<'_> {
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.index + offset).copied()
+    }
+
+    fn starts_with(&self, value: &str) -> bool {
+        value
+            .chars()
+            .enumerate()
+            .all(|(offset, char)| self.peek(offset) == Some(char))
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let char = self.peek(0)?;
+        self.index += 1;
+
+        if char == '\n' {
+            self.line += 1;
+            self.column = 0;
+        } else {
+            self.column += char.len_utf8() as u32;
+        }
+
+        Some(char)
+    }
+
+    fn bump_while(&mut self, condition: impl Fn(char) -> bool) {
+        while let Some(char) = self.peek(0) {
+            if !condition(char) {
+                break;
+            }
+            self.bump();
+        }
+    }
+
+    /// Source text between two positions, the end column is exclusive.
+    fn text(&self, start_line: u32, start_column: u32) -> String {
+        if start_line == self.line {
+            return self.lines[start_line as usize][start_column as usize..self.column as usize]
+                .to_string();
+        }
+
+        let mut text = self.lines[start_line as usize][start_column as usize..].to_string();
+
+        for line in start_line + 1..self.line {
+            text.push('\n');
+            text.push_str(&self.lines[line as usize]);
+        }
+
+        text.push('\n');
+        text.push_str(&self.lines[self.line as usize][..self.column as usize]);
+
+        text
+    }
+}
+
+/// Splits C# source into tokens, every token keeps the line and byte column
+/// range it was read from so later passes can map back onto `Data.lines`.
+pub fn tokenize(lines: &[String]) -> Vec<Token> {
+    let mut cursor = Cursor {
+        chars: lines.join("\n").chars().collect(),
+        lines,
+        index: 0,
+        line: 0,
+        column: 0,
+    };
+
+    // Editors save C# files with a byte order mark, it stays in `lines` so
+    // columns keep indexing into them but never becomes a token.
+    if cursor.peek(0) == Some('\u{FEFF}') {
+        cursor.bump();
+    }
+
+    let mut tokens = vec![];
+
+    while let Some(char) = cursor.peek(0) {
+        if char.is_whitespace() {
+            cursor.bump();
+            continue;
+        }
+
+        let start_line = cursor.line;
+        let start_column = cursor.column;
+        let kind = read_token(&mut cursor, char);
+
+        tokens.push(Token {
+            kind,
+            text: cursor.text(start_line, start_column),
+            span: Span {
+                start_line,
+                start_column,
+                end_line: cursor.line,
+                end_column: cursor.column,
+            },
+        });
+    }
+
+    tokens
+}
+
+fn read_token(cursor: &mut Cursor, char: char) -> TokenKind {
+    if cursor.starts_with("//") {
+        cursor.bump_while(|char| char != '\n');
+        return TokenKind::Comment;
+    }
+
+    if cursor.starts_with("/*") {
+        cursor.bump();
+        cursor.bump();
+
+        while cursor.peek(0).is_some() && !cursor.starts_with("*/") {
+            cursor.bump();
+        }
+
+        cursor.bump();
+        cursor.bump();
+        return TokenKind::Comment;
+    }
+
+    if char == '#' && is_line_start(cursor) {
+        cursor.bump_while(|char| char != '\n');
+        return TokenKind::Directive;
+    }
+
+    let prefix_length = ["$@\"", "@$\"", "$\"", "@\""]
+        .iter()
+        .find(|prefix| cursor.starts_with(prefix))
+        .map(|prefix| prefix.len() - 1);
+
+    if let Some(prefix_length) = prefix_length {
+        let interpolated = char == '$' || cursor.peek(1) == Some('$');
+        let verbatim = char == '@' || cursor.peek(1) == Some('@');
+
+        for _ in 0..prefix_length {
+            cursor.bump();
+        }
+
+        read_string(cursor, verbatim, interpolated);
+
+        return if interpolated {
+            TokenKind::InterpolatedString
+        } else {
+            TokenKind::VerbatimString
+        };
+    }
+
+    if cursor.starts_with("\"\"\"") {
+        read_raw_string(cursor);
+        return TokenKind::String;
+    }
+
+    if char == '"' {
+        read_string(cursor, false, false);
+        return TokenKind::String;
+    }
+
+    if char == '\'' {
+        cursor.bump();
+
+        while let Some(char) = cursor.bump() {
+            if char == '\\' {
+                cursor.bump();
+            } else if char == '\'' || char == '\n' {
+                break;
+            }
+        }
+
+        return TokenKind::Char;
+    }
+
+    if char.is_ascii_digit() || (char == '.' && cursor.peek(1).is_some_and(|c| c.is_ascii_digit()))
+    {
+        cursor.bump_while(|char| char.is_ascii_alphanumeric() || char == '_' || char == '.');
+        return TokenKind::Number;
+    }
+
+    if char.is_alphabetic() || char == '_' || char == '@' {
+        let start = cursor.index;
+        cursor.bump();
+        cursor.bump_while(|char| char.is_alphanumeric() || char == '_');
+
+        let word = cursor.chars[start..cursor.index].iter().collect::<String>();
+
+        return if KEYWORDS.contains(&word.as_str()) {
+            TokenKind::Keyword
+        } else {
+            TokenKind::Identifier
+        };
+    }
+
+    let operator = OPERATORS
+        .iter()
+        .find(|operator| cursor.starts_with(operator));
+
+    match operator {
+        Some(operator) => {
+            for _ in 0..operator.len() {
+                cursor.bump();
+            }
+        }
+        None => {
+            cursor.bump();
+        }
+    }
+
+    TokenKind::Punctuation
+}
+
+fn is_line_start(cursor: &Cursor) -> bool {
+    cursor.lines[cursor.line as usize][..cursor.column as usize]
+        .trim_start_matches('\u{FEFF}')
+        .trim()
+        .is_empty()
+}
+
+/// Reads a regular, verbatim or interpolated string starting at its opening
+/// quote, interpolation holes may contain nested braces and strings.
+fn read_string(cursor: &mut Cursor, verbatim: bool, interpolated: bool) {
+    cursor.bump();
+
+    while let Some(char) = cursor.bump() {
+        match char {
+            '\\' if !verbatim => {
+                cursor.bump();
+            }
+            '"' if verbatim && cursor.peek(0) == Some('"') => {
+                cursor.bump();
+            }
+            '"' => break,
+            '\n' if !verbatim => break,
+            '{' if interpolated && cursor.peek(0) == Some('{') => {
+                cursor.bump();
+            }
+            '{' if interpolated => read_interpolation(cursor),
+            _ => (),
+        }
+    }
+}
+
+fn read_interpolation(cursor: &mut Cursor) {
+    let mut depth = 1;
+
+    while let Some(char) = cursor.peek(0) {
+        match char {
+            '"' => {
+                read_string(cursor, false, false);
+                continue;
+            }
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+
+                if depth == 0 {
+                    cursor.bump();
+                    return;
+                }
+            }
+            _ => (),
+        }
+
+        cursor.bump();
+    }
+}
+
+fn read_raw_string(cursor: &mut Cursor) {
+    let mut quotes = 0;
+
+    while cursor.peek(0) == Some('"') {
+        cursor.bump();
+        quotes += 1;
+    }
+
+    let closing = "\"".repeat(quotes);
+
+    while cursor.peek(0).is_some() {
+        if cursor.starts_with(&closing) {
+            for _ in 0..quotes {
+                cursor.bump();
+            }
+            return;
+        }
+
+        cursor.bump();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::tokenize;
+    use crate::{Span, TokenKind};
+
+    fn lex(source: &str) -> Vec<(TokenKind, String)> {
+        let lines = source
+            .lines()
+            .map(|x| x.to_string())
+            .collect::<Vec<String>>();
+
+        tokenize(&lines)
+            .into_iter()
+            .map(|token| (token.kind, token.text))
+            .collect()
+    }
+
+    #[test]
+    fn tokenizes_declarations() {
+        let tokens = lex("public\tList<UserTask> Get(Guid id) {");
+
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Keyword, "public".to_string()),
+                (TokenKind::Identifier, "List".to_string()),
+                (TokenKind::Punctuation, "<".to_string()),
+                (TokenKind::Identifier, "UserTask".to_string()),
+                (TokenKind::Punctuation, ">".to_string()),
+                (TokenKind::Identifier, "Get".to_string()),
+                (TokenKind::Punctuation, "(".to_string()),
+                (TokenKind::Identifier, "Guid".to_string()),
+                (TokenKind::Identifier, "id".to_string()),
+                (TokenKind::Punctuation, ")".to_string()),
+                (TokenKind::Punctuation, "{".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenizes_literals_and_comments() {
+        let tokens = lex(concat!(
+            "var a = \"{ \\\" }\"; // { comment\n",
+            "var b = @\"c:\\{\"\"\"; /* } */\n",
+            "var c = $\"{x.Name} {{ {(y ? \"}\" : \"\")}\";\n",
+            "var d = '}'; var e = 1.5m; #\n",
+            "  #region x",
+        ));
+
+        let kinds = tokens
+            .iter()
+            .filter(|(kind, _)| *kind != TokenKind::Punctuation)
+            .filter(|(_, text)| !["var", "a", "b", "c", "d", "e"].contains(&text.as_str()))
+            .cloned()
+            .collect::<Vec<(TokenKind, String)>>();
+
+        assert_eq!(
+            kinds,
+            vec![
+                (TokenKind::String, "\"{ \\\" }\"".to_string()),
+                (TokenKind::Comment, "// { comment".to_string()),
+                (TokenKind::VerbatimString, "@\"c:\\{\"\"\"".to_string()),
+                (TokenKind::Comment, "/* } */".to_string()),
+                (
+                    TokenKind::InterpolatedString,
+                    "$\"{x.Name} {{ {(y ? \"}\" : \"\")}\"".to_string()
+                ),
+                (TokenKind::Char, "'}'".to_string()),
+                (TokenKind::Number, "1.5m".to_string()),
+                (TokenKind::Directive, "#region x".to_string()),
+            ]
+        );
+
+        assert!(!tokens
+            .iter()
+            .any(|(kind, text)| *kind == TokenKind::Punctuation && (text == "{" || text == "}")));
+    }
+
+    #[test]
+    fn skips_byte_order_mark() {
+        let lines = vec![
+            "\u{FEFF}#nullable enable".to_string(),
+            "using System;".to_string(),
+        ];
+
+        let tokens = tokenize(&lines);
+
+        assert_eq!(tokens[0].kind, TokenKind::Directive);
+        assert_eq!(tokens[0].text, "#nullable enable");
+        assert_eq!(tokens[0].span.start_column, 3);
+        assert_eq!(tokens[1].text, "using");
+    }
+
+    #[test]
+    fn tracks_spans_across_lines() {
+        let lines = vec![
+            "if (x == 1)".to_string(),
+            "    /* a".to_string(),
+            "       b */ {".to_string(),
+        ];
+
+        let tokens = tokenize(&lines);
+
+        assert_eq!(
+            tokens[3].span,
+            Span {
+                start_line: 0,
+                start_column: 6,
+                end_line: 0,
+                end_column: 8,
+            }
+        );
+        assert_eq!(tokens[6].kind, TokenKind::Comment);
+        assert_eq!(
+            tokens[6].span,
+            Span {
+                start_line: 1,
+                start_column: 4,
+                end_line: 2,
+                end_column: 11,
+            }
+        );
+        assert_eq!(tokens[6].text, "/* a\n       b */");
+        assert_eq!(tokens[7].span.start_line, 2);
+        assert_eq!(tokens[7].span.start_column, 12);
+    }
+}
@@ -0,0 +1,658 @@
+//! Converts ASP.NET controllers into TypeScript routers.
+//!
+//! [`analyze_source`] reads a C# controller into an [`Analysis`], which the
+//! generators in [`generate`] turn into a tRPC, express or plain module router.
+//!
+//! ```
+//! use controller_to_router_rs::{analyze_source, generate::Target};
+//!
+//! let analysis = analyze_source("public class TestController : ControllerBase\n{\n}");
+//! let output = analysis.generate(&Target::Trpc);
+//!
+//! assert!(output.contains("export const testRouter = router({"));
+//! ```
+
+pub mod analyze;
+pub mod diagnostics;
+pub mod generate;
+pub mod lexer;
+pub mod project;
+pub mod report;
+pub mod route;
+pub mod types;
+
+use std::collections::HashMap;
+
+use generate::Target;
+use serde::{Deserialize, Serialize};
+
+/// Analyzes the source of a C# controller. Problems are reported as
+/// diagnostics and never stop the analysis, so the rest of the file is still
+/// read.
+pub fn analyze_source(source: &str) -> Analysis {
+    let data = Data {
+        lines: source
+            .lines()
+            .map(|x| x.to_string())
+            .collect::<Vec<String>>(),
+        class_name: None,
+        blocks: None,
+        route_prefix: None,
+        class_attributes: vec![],
+        tokens: vec![],
+        line_numbers: vec![],
+        diagnostics: vec![],
+        models: vec![],
+        classes: vec![],
+    };
+
+    Analysis {
+        data: analyze::analyze_lines(data),
+    }
+}
+
+/// An analyzed controller.
+#[derive(Clone, Debug)]
+pub struct Analysis {
+    pub data: Data,
+}
+
+impl Analysis {
+    pub fn class_name(&self) -> Option<&str> {
+        self.data.class_name.as_deref()
+    }
+
+    pub fn blocks(&self) -> &[Block] {
+        self.data.blocks.as_deref().unwrap_or_default()
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.data.diagnostics
+    }
+
+    /// The models declared next to the controller.
+    pub fn models(&self) -> &[Model] {
+        &self.data.models
+    }
+
+    /// Every class of the file, nested classes included.
+    pub fn classes(&self) -> &[Class] {
+        &self.data.classes
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics()
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+
+    /// The analysis in the shape it is written to JSON and YAML.
+    pub fn report(&self) -> Report {
+        report::get_report(&self.data)
+    }
+
+    pub fn to_json(&self) -> String {
+        report::to_json(&self.report())
+    }
+
+    pub fn to_yaml(&self) -> String {
+        report::to_yaml(&self.report())
+    }
+
+    /// Writes the router for the given target.
+    pub fn generate(&self, target: &Target) -> String {
+        self.generate_with_types(target, &TypeMap::default())
+    }
+
+    /// Writes the router for the given target, resolving types with the
+    /// overrides of a type map first.
+    pub fn generate_with_types(&self, target: &Target, types: &TypeMap) -> String {
+        generate::generate(&self.data, target, types)
+    }
+}
+
+/// The serialized analysis. `schema_version` is raised whenever a field is
+/// renamed or removed, and the lines of blocks are 1-based source lines like
+/// the lines of diagnostics.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Report {
+    pub schema_version: u32,
+    pub class_name: Option<String>,
+    pub route_prefix: Option<String>,
+    pub class_attributes: Vec<String>,
+    pub blocks: Vec<Block>,
+    pub diagnostics: Vec<Diagnostic>,
+    pub models: Vec<Model>,
+    pub classes: Vec<Class>,
+}
+
+/// What a `{ ... }` block of the source was recognized as.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockType {
+    Namespace,
+    Class,
+    Constructor,
+    Method,
+    Context,
+    Variable,
+    Select,
+    Write,
+    If,
+    Unknown,
+}
+
+/// A block of the source, `start` and `end` are the lines of its braces.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Block {
+    pub start: u32,
+    pub end: Option<u32>,
+    pub block_type: BlockType,
+    pub details: Option<BlockDetails>,
+}
+
+/// A class of the file, `start` and `end` are the lines of its braces.
+/// `parent` names the class a nested class is declared in, and `class_name`
+/// of the analysis is the first class that is a controller.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Class {
+    pub name: String,
+    pub namespace: Option<String>,
+    pub parent: Option<String>,
+    pub base_types: Vec<String>,
+    pub attributes: Vec<String>,
+    pub route_prefix: Option<String>,
+    pub members: Vec<Member>,
+    pub start: u32,
+    pub end: Option<u32>,
+}
+
+/// A member declared directly in a class, nested types are classes of their
+/// own.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Member {
+    pub name: String,
+    pub kind: MemberKind,
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemberKind {
+    Constructor,
+    Method,
+    Property,
+    Field,
+}
+
+/// The state the analysis passes hand to each other, the generators read the
+/// analyzed blocks from it.
+#[derive(Clone, Debug)]
+pub struct Data {
+    pub lines: Vec<String>,
+    pub class_name: Option<String>,
+    pub blocks: Option<Vec<Block>>,
+    pub route_prefix: Option<String>,
+    pub class_attributes: Vec<String>,
+    pub tokens: Vec<Token>,
+    pub line_numbers: Vec<u32>,
+    pub diagnostics: Vec<Diagnostic>,
+    pub models: Vec<Model>,
+    pub classes: Vec<Class>,
+}
+
+/// What the analysis read from a block, depending on its type.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BlockDetails {
+    ContextBlock {
+        variable: String,
+    },
+    MethodBlock {
+        name: String,
+        http_method: Option<HttpType>,
+        route: Option<Route>,
+        variables: Vec<Variable>,
+        uses_context: bool,
+    },
+    VariableBlock {
+        name: String,
+        data_type: String,
+        assignments: Vec<Assignment>,
+    },
+    SelectBlock {
+        query_type: QueryType,
+        tables: Vec<Table>,
+        where_clauses: Vec<WhereClause>,
+        return_data: Vec<ReturnData>,
+        syntax: LinqSyntax,
+        has_return: bool,
+        operators: Vec<LinqOperator>,
+        order_by: Vec<OrderKey>,
+        skip: Option<String>,
+        take: Option<String>,
+        aggregate: Option<Aggregate>,
+        projection: Option<Projection>,
+    },
+    IfBlock {
+        clause: String,
+        is_else: bool,
+    },
+    InsertBlock {
+        table: String,
+        variable: String,
+        assignments: Vec<Assignment>,
+        many: bool,
+    },
+    DeleteBlock {
+        table: String,
+        variable: String,
+        where_clauses: Vec<WhereClause>,
+        many: bool,
+    },
+    UpdateBlock {
+        table: String,
+        variable: String,
+        where_clauses: Vec<WhereClause>,
+        assignments: Vec<Assignment>,
+    },
+    SaveChangesBlock {
+        variable: String,
+    },
+}
+
+/// The http method a controller action is mapped to.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HttpType {
+    HttpGet,
+    HttpPost,
+    HttpPut,
+    HttpPatch,
+    HttpDelete,
+}
+
+/// The route of a controller action, `path` has the class prefix and the
+/// `[controller]`/`[action]` tokens resolved.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Route {
+    pub template: String,
+    pub path: String,
+    pub parameters: Vec<RouteParameter>,
+}
+
+/// A `{name:constraint=default}` segment of a route template.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct RouteParameter {
+    pub name: String,
+    pub constraints: Vec<String>,
+    pub optional: bool,
+    pub default_value: Option<String>,
+    pub catch_all: bool,
+}
+
+/// A method parameter, with the C# value it defaults to like the `1` of
+/// `int page = 1`.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Variable {
+    pub name: String,
+    pub variable_type: String,
+    #[serde(default)]
+    pub default_value: Option<String>,
+}
+
+/// How many rows a query returns. `First()` and `Single()` throw when there
+/// is no row, their `OrDefault` variants return null.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryType {
+    Many,
+    First,
+    FirstOrThrow,
+    Unique,
+    UniqueOrThrow,
+}
+
+/// A key a query is sorted by, from `orderby` or `.OrderBy(...)` and the
+/// `ThenBy` calls after it.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct OrderKey {
+    pub key: Expression,
+    pub direction: SortDirection,
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+/// A query that returns a single value computed from its rows, `selector` is
+/// the column `Sum`, `Max`, `Min` and `Average` read.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Aggregate {
+    pub kind: AggregateKind,
+    pub selector: Option<Expression>,
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregateKind {
+    Count,
+    Any,
+    Sum,
+    Max,
+    Min,
+    Average,
+}
+
+/// Whether a query is written with query keywords, lambda calls or both.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinqSyntax {
+    Lambda,
+    Query,
+    Both,
+}
+
+/// A call of a query's method chain, e.g. `.OrderBy(t => t.OrderNumber)`,
+/// with the lambda body or the arguments as written.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct LinqOperator {
+    pub kind: OperatorKind,
+    pub method: String,
+    pub lambda_variable: Option<String>,
+    pub argument: String,
+}
+
+/// What a query operator does, `Async` variants have the kind of the method
+/// they wrap. Terminal operators run the query, like `ToList` or
+/// `FirstOrDefault`.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperatorKind {
+    Where,
+    Select,
+    OrderBy,
+    OrderByDescending,
+    ThenBy,
+    ThenByDescending,
+    Skip,
+    Take,
+    Include,
+    ThenInclude,
+    Any,
+    Count,
+    Sum,
+    Max,
+    Min,
+    Average,
+    Distinct,
+    GroupBy,
+    Terminal,
+    Other,
+}
+
+/// A property of the object a query projects to and the column it is read
+/// from.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct ReturnData {
+    pub table: String,
+    pub property: String,
+    pub value: String,
+}
+
+/// What a query selects, read from `select ...` or the last `.Select(...)`.
+/// Computed values keep their source text and the columns they read, as
+/// parallel `shortcuts` and `properties`.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Projection {
+    /// `select ut`, the whole row.
+    Row { shortcut: String },
+    /// `ut.Name` or `ut.User.Name`.
+    Column { shortcut: String, path: Vec<String> },
+    Computed {
+        expression: String,
+        shortcuts: Vec<String>,
+        properties: Vec<String>,
+    },
+    /// `new UserDto { ... }`, or `new { ... }` when `type_name` is missing.
+    Object {
+        type_name: Option<String>,
+        fields: Vec<ProjectedField>,
+    },
+    /// `u.Tasks.Where(...).Select(t => ...).ToList()`, `element` is what the
+    /// last `Select` makes of each item.
+    Collection {
+        shortcut: String,
+        path: Vec<String>,
+        operators: Vec<LinqOperator>,
+        element: Option<Box<Projection>>,
+    },
+}
+
+/// A property of a projected object, `Name = ut.Name` or the shorthand
+/// `ut.Name` of anonymous types.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct ProjectedField {
+    pub name: String,
+    pub value: Projection,
+}
+
+/// A table a query reads from and the shortcut it is referred to with.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Table {
+    pub name: String,
+    pub shortcut: String,
+    pub joined_tables: Vec<Table>,
+    pub return_frequency: i32,
+    pub join: Option<Join>,
+}
+
+/// How a `join ... on ... equals ...` clause connects a table to the tables
+/// before it.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Join {
+    pub kind: JoinKind,
+    pub keys: Vec<JoinKey>,
+    pub group: Option<String>,
+}
+
+/// `ut.UserOid equals u.UserOid`, the column of a table already in the query
+/// and the column of the joined table it equals.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct JoinKey {
+    pub shortcut: String,
+    pub property: String,
+    pub joined_property: String,
+}
+
+/// A plain `join`, a group join with `into` or a left join, which is a group
+/// join read again with `from x in group.DefaultIfEmpty()`.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JoinKind {
+    Inner,
+    Group,
+    Left,
+}
+
+/// A filter of a query.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct WhereClause {
+    pub shortcut: Vec<String>,
+    pub property: Vec<String>,
+    pub value: String,
+    pub lambda_varible: Option<String>,
+    pub expression: Option<Expression>,
+}
+
+/// A condition read into a tree. Operands say whether they read a column of
+/// the query, a parameter, a local or a constant, anything else is kept as
+/// its source text.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Expression {
+    Binary {
+        operator: String,
+        left: Box<Expression>,
+        right: Box<Expression>,
+    },
+    Not {
+        operand: Box<Expression>,
+    },
+    Column {
+        shortcut: String,
+        path: Vec<String>,
+    },
+    Parameter {
+        name: String,
+        path: Vec<String>,
+    },
+    Variable {
+        name: String,
+        path: Vec<String>,
+    },
+    Literal {
+        value: String,
+    },
+    Call {
+        target: Box<Expression>,
+        method: String,
+        arguments: Vec<Expression>,
+    },
+    Raw {
+        text: String,
+    },
+}
+
+/// A value written to a property, by an object initializer or an assignment
+/// to a tracked entity.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Assignment {
+    pub property: String,
+    pub value: String,
+    pub references: Vec<Reference>,
+}
+
+/// Something an expression reads: a method parameter or local variable with
+/// the members accessed on it, or a static member or call like
+/// `DateTime.UtcNow` and `Guid.NewGuid()`.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Reference {
+    Parameter { name: String, path: Vec<String> },
+    Variable { name: String, path: Vec<String> },
+    Member { name: String },
+    Call { name: String },
+}
+
+/// A class, record or struct with public auto-properties, such as the DTOs
+/// and entities a controller reads and returns.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Model {
+    pub name: String,
+    pub properties: Vec<Property>,
+}
+
+/// A public `{ get; set; }` property of a model.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Property {
+    pub name: String,
+    pub property_type: String,
+}
+
+/// Custom C# types mapped to TypeScript, read from a type map file. They are
+/// looked up before the built in types.
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct TypeMap {
+    pub overrides: HashMap<String, TypeOverride>,
+}
+
+/// The TypeScript type of a custom C# type, with the zod schema that checks
+/// it when the TypeScript type alone does not say.
+#[derive(PartialEq, Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum TypeOverride {
+    Ts(String),
+    Full { ts: String, zod: Option<String> },
+}
+
+// pub struct ContextBlock extends Block {
+//     pub start: u32,
+//     pub end: Option<u32>,
+//     pub block_type: BlockType,
+// }
+
+// export type ContextBlock = Block & {
+//     type: "context";
+//     variable: string;
+//   };
+
+/// The kind of a lexer token.
+#[derive(PartialEq, Clone, Debug)]
+pub enum TokenKind {
+    Identifier,
+    Keyword,
+    String,
+    VerbatimString,
+    InterpolatedString,
+    Char,
+    Number,
+    Comment,
+    Directive,
+    Punctuation,
+}
+
+/// The lines and byte columns a token was read from, the end column is
+/// exclusive.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Span {
+    pub start_line: u32,
+    pub start_column: u32,
+    pub end_line: u32,
+    pub end_column: u32,
+}
+
+/// A token of the C# source.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub text: String,
+    pub span: Span,
+}
+
+/// How serious a diagnostic is, errors mean part of the file was skipped or
+/// guessed.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// The problem a diagnostic reports.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum DiagnosticCode {
+    #[serde(rename = "CTR001")]
+    UnbalancedBraces,
+    #[serde(rename = "CTR002")]
+    MissingClass,
+    #[serde(rename = "CTR003")]
+    UnsupportedHttpMethod,
+    #[serde(rename = "CTR004")]
+    UnreadableMethod,
+    #[serde(rename = "CTR005")]
+    UnreadableQuery,
+}
+
+/// A problem found while analyzing, `line` is the 1-based line in the source
+/// file when the problem has one.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub code: DiagnosticCode,
+    pub severity: Severity,
+    pub line: Option<u32>,
+    pub message: String,
+}
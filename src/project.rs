@@ -0,0 +1,179 @@
+use std::path::{Path, PathBuf};
+
+/// Directories that only hold build output or tooling and never controllers.
+const SKIPPED_DIRECTORIES: &[&str] = &["bin", "obj", "node_modules"];
+
+pub fn is_project_file(path: &Path) -> bool {
+    path.is_file() && path.extension().is_some_and(|ext| ext == "csproj")
+}
+
+pub fn is_controller_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.ends_with("Controller.cs"))
+}
+
+/// The controllers to convert for an input path, sorted by path. A directory
+/// is searched recursively for `*Controller.cs` files and a `.csproj` yields
+/// the controllers it compiles: everything below its directory for SDK style
+/// projects and the `<Compile Include>` items for the older format.
+pub fn find_controllers(path: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut files = if is_project_file(path) {
+        project_controllers(path)?
+    } else if path.is_dir() {
+        let mut files = vec![];
+        find_in_directory(path, is_controller_file, &mut files)?;
+        files
+    } else {
+        vec![path.to_path_buf()]
+    };
+
+    files.sort();
+    files.dedup();
+
+    Ok(files)
+}
+
+/// Every `.cs` file below a directory, sorted by path, used to read models.
+pub fn find_sources(directory: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut files = vec![];
+    find_in_directory(
+        directory,
+        |path| path.extension().is_some_and(|ext| ext == "cs"),
+        &mut files,
+    )?;
+
+    files.sort();
+
+    Ok(files)
+}
+
+fn find_in_directory(
+    directory: &Path,
+    include: fn(&Path) -> bool,
+    files: &mut Vec<PathBuf>,
+) -> Result<(), String> {
+    let entries = std::fs::read_dir(directory)
+        .map_err(|error| format!("could not read {}: {}", directory.display(), error))?;
+
+    for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+
+        if path.is_dir() {
+            if !name.starts_with('.') && !SKIPPED_DIRECTORIES.contains(&name) {
+                find_in_directory(&path, include, files)?;
+            }
+        } else if include(&path) {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+fn project_controllers(project: &Path) -> Result<Vec<PathBuf>, String> {
+    let source = std::fs::read_to_string(project)
+        .map_err(|error| format!("could not read {}: {}", project.display(), error))?;
+    let directory = project.parent().unwrap_or(Path::new("."));
+
+    let mut files = compile_includes(&source)
+        .iter()
+        .filter(|include| !include.contains('*'))
+        .map(|include| directory.join(include.replace('\\', "/")))
+        .filter(|path| is_controller_file(path) && path.is_file())
+        .collect::<Vec<PathBuf>>();
+
+    if is_sdk_project(&source) {
+        find_in_directory(directory, is_controller_file, &mut files)?;
+    }
+
+    Ok(files)
+}
+
+/// SDK style projects compile every `.cs` file below them without listing it.
+fn is_sdk_project(source: &str) -> bool {
+    source
+        .split("<Project")
+        .nth(1)
+        .and_then(|rest| rest.split('>').next())
+        .is_some_and(|attributes| attributes.contains("Sdk="))
+}
+
+/// The `Include` values of every `<Compile>` item in a project file.
+pub fn compile_includes(source: &str) -> Vec<String> {
+    source
+        .split("<Compile")
+        .skip(1)
+        .filter_map(|item| {
+            let attributes = item.split('>').next()?;
+            let (_, value) = attributes.split_once("Include=\"")?;
+            let (value, _) = value.split_once('"')?;
+
+            Some(value.to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::{compile_includes, find_controllers, find_sources};
+
+    #[test]
+    fn finds_controllers_in_directories() {
+        let files = find_controllers(Path::new("./tests/mocks/project")).unwrap();
+
+        assert_eq!(
+            files,
+            vec![
+                PathBuf::from("./tests/mocks/project/Controllers/Admin/UsersController.cs"),
+                PathBuf::from("./tests/mocks/project/Controllers/OrdersController.cs"),
+                PathBuf::from("./tests/mocks/project/Controllers/TestController.cs"),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_sources_below_directories() {
+        let files = find_sources(Path::new("./tests/mocks/project")).unwrap();
+
+        assert_eq!(files.len(), 4);
+        assert!(files.contains(&PathBuf::from("./tests/mocks/project/Models/Order.cs")));
+    }
+
+    #[test]
+    fn finds_controllers_in_projects() {
+        let files = find_controllers(Path::new("./tests/mocks/project/Api.csproj")).unwrap();
+        assert_eq!(files.len(), 3);
+
+        let files = find_controllers(Path::new("./tests/mocks/project/Legacy.csproj")).unwrap();
+        assert_eq!(
+            files,
+            vec![PathBuf::from(
+                "./tests/mocks/project/Controllers/OrdersController.cs"
+            )]
+        );
+    }
+
+    #[test]
+    fn reads_compile_items() {
+        let includes = compile_includes(concat!(
+            "<ItemGroup>\n",
+            "  <Compile Include=\"Controllers\\OrdersController.cs\" />\n",
+            "  <Compile Remove=\"Old\\**\" />\n",
+            "  <Compile Include=\"Models\\Order.cs\">\n",
+            "    <SubType>Code</SubType>\n",
+            "  </Compile>\n",
+            "</ItemGroup>",
+        ));
+
+        assert_eq!(
+            includes,
+            vec!["Controllers\\OrdersController.cs", "Models\\Order.cs"]
+        );
+    }
+}
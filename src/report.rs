@@ -0,0 +1,104 @@
+use crate::{Block, Class, Data, Report};
+
+/// Raised whenever a field of the report is renamed, removed or changes
+/// meaning, new fields keep the version.
+pub const SCHEMA_VERSION: u32 = 2;
+
+pub fn get_report(data: &Data) -> Report {
+    let source_line = |line: u32| {
+        data.line_numbers
+            .get(line as usize)
+            .copied()
+            .unwrap_or(line)
+            + 1
+    };
+
+    let blocks = data
+        .blocks
+        .iter()
+        .flatten()
+        .map(|block| Block {
+            start: source_line(block.start),
+            end: block.end.map(source_line),
+            ..block.clone()
+        })
+        .collect::<Vec<Block>>();
+
+    let classes = data
+        .classes
+        .iter()
+        .map(|class| Class {
+            start: source_line(class.start),
+            end: class.end.map(source_line),
+            ..class.clone()
+        })
+        .collect::<Vec<Class>>();
+
+    Report {
+        schema_version: SCHEMA_VERSION,
+        class_name: data.class_name.clone(),
+        route_prefix: data.route_prefix.clone(),
+        class_attributes: data.class_attributes.clone(),
+        blocks,
+        diagnostics: data.diagnostics.clone(),
+        models: data.models.clone(),
+        classes,
+    }
+}
+
+pub fn to_json(report: &Report) -> String {
+    serde_json::to_string_pretty(report).expect("the report only has string keys")
+}
+
+pub fn to_yaml(report: &Report) -> String {
+    serde_yaml::to_string(report).expect("the report only has string keys")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SCHEMA_VERSION;
+    use crate::{analyze_source, BlockType, Report};
+
+    #[test]
+    fn serializes_the_analysis() {
+        let input = std::fs::read_to_string("./tests/mocks/input3.cs").unwrap();
+        let analysis = analyze_source(&input);
+
+        let json = analysis.to_json();
+        let value = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+        assert_eq!(value["class_name"], "TestController");
+        assert_eq!(value["route_prefix"], "api/[controller]");
+
+        let method = value["blocks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|block| block["details"]["name"] == "getClientTodoTasks")
+            .unwrap();
+
+        assert_eq!(method["block_type"], "method");
+        assert_eq!(method["details"]["kind"], "method_block");
+        assert_eq!(method["details"]["http_method"], "http_get");
+        assert_eq!(
+            method["details"]["route"]["path"],
+            "/api/Test/todoUserTasks/{userOid}"
+        );
+
+        let report = serde_json::from_str::<Report>(&json).unwrap();
+        assert_eq!(report, analysis.report());
+        assert_eq!(
+            serde_yaml::from_str::<Report>(&analysis.to_yaml()).unwrap(),
+            report
+        );
+
+        // Same-line braces are reported on the line of their declaration.
+        let class = report
+            .blocks
+            .iter()
+            .find(|block| block.block_type == BlockType::Class)
+            .unwrap();
+        assert_eq!(class.start, 14);
+    }
+}
@@ -0,0 +1,216 @@
+use crate::{Route, RouteParameter};
+
+/// Resolves a method route against the class level `[Route]` prefix the way
+/// ASP.NET does: templates starting with `/` or `~/` ignore the prefix and
+/// `[controller]`/`[action]` are replaced with the controller and method names.
+pub fn parse_route(
+    route_prefix: &Option<String>,
+    template: &Option<String>,
+    class_name: &Option<String>,
+    method_name: &str,
+) -> Route {
+    let template = template.clone().unwrap_or_default();
+
+    let path = if template.starts_with('/') || template.starts_with("~/") {
+        template.trim_start_matches('~').to_string()
+    } else {
+        [route_prefix.clone().unwrap_or_default(), template.clone()]
+            .iter()
+            .map(|part| part.trim_matches('/').to_string())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<String>>()
+            .join("/")
+    };
+
+    let controller = class_name
+        .clone()
+        .unwrap_or_default()
+        .trim_end_matches("Controller")
+        .to_string();
+
+    let path = replace_token(&path, "[controller]", &controller);
+    let path = replace_token(&path, "[action]", method_name);
+
+    Route {
+        parameters: get_route_parameters(&path),
+        path: format!("/{}", path.trim_start_matches('/')),
+        template,
+    }
+}
+
+/// Replaces a route token ignoring its case, ASP.NET reads `[Controller]`
+/// like `[controller]`.
+fn replace_token(path: &str, token: &str, value: &str) -> String {
+    let lowercase = path.to_ascii_lowercase();
+    let mut result = String::new();
+    let mut last = 0;
+
+    for (index, _) in lowercase.match_indices(token) {
+        result.push_str(&path[last..index]);
+        result.push_str(value);
+        last = index + token.len();
+    }
+
+    result.push_str(&path[last..]);
+    result
+}
+
+pub fn get_route_parameters(template: &str) -> Vec<RouteParameter> {
+    let mut parameters = vec![];
+    let mut current: Option<String> = None;
+    let mut depth = 0;
+
+    for char in template.chars() {
+        match (char, &mut current) {
+            ('{', None) => current = Some(String::new()),
+            ('(', Some(parameter)) => {
+                depth += 1;
+                parameter.push(char);
+            }
+            (')', Some(parameter)) => {
+                depth -= 1;
+                parameter.push(char);
+            }
+            ('}', Some(parameter)) if depth == 0 => {
+                parameters.push(get_route_parameter(parameter));
+                current = None;
+            }
+            (char, Some(parameter)) => parameter.push(char),
+            _ => (),
+        }
+    }
+
+    parameters
+}
+
+/// Parses the inside of a `{...}` segment such as `id:int:min(1)`, `id?`,
+/// `page=1` or `*path`.
+fn get_route_parameter(segment: &str) -> RouteParameter {
+    let mut segment = segment.trim().to_string();
+
+    let catch_all = segment.starts_with('*');
+    segment = segment.trim_start_matches('*').to_string();
+
+    let optional = segment.ends_with('?');
+    segment = segment.trim_end_matches('?').to_string();
+
+    let mut default_value = None;
+    if let Some((rest, value)) = segment.split_once('=') {
+        default_value = Some(value.to_string());
+        segment = rest.to_string();
+    }
+
+    let mut parts = vec![];
+    let mut part = String::new();
+    let mut depth = 0;
+
+    for char in segment.chars() {
+        match char {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => (),
+        }
+
+        if char == ':' && depth == 0 {
+            parts.push(part);
+            part = String::new();
+        } else {
+            part.push(char);
+        }
+    }
+    parts.push(part);
+
+    RouteParameter {
+        name: parts[0].clone(),
+        constraints: parts[1..].to_vec(),
+        optional,
+        default_value,
+        catch_all,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_route_parameters, parse_route};
+    use crate::RouteParameter;
+
+    fn parameter(name: &str) -> RouteParameter {
+        RouteParameter {
+            name: name.to_string(),
+            constraints: vec![],
+            optional: false,
+            default_value: None,
+            catch_all: false,
+        }
+    }
+
+    #[test]
+    fn resolves_prefix_and_tokens() {
+        let prefix = Some("api/[controller]".to_string());
+        let class_name = Some("TestController".to_string());
+
+        let route = parse_route(&prefix, &None, &class_name, "AddAdmin");
+        assert_eq!(route.template, "");
+        assert_eq!(route.path, "/api/Test");
+        assert_eq!(route.parameters, vec![]);
+
+        let route = parse_route(
+            &prefix,
+            &Some("todoUserTasks/{userOid}".to_string()),
+            &class_name,
+            "getClientTodoTasks",
+        );
+        assert_eq!(route.template, "todoUserTasks/{userOid}");
+        assert_eq!(route.path, "/api/Test/todoUserTasks/{userOid}");
+        assert_eq!(route.parameters, vec![parameter("userOid")]);
+
+        let route = parse_route(
+            &prefix,
+            &Some("~/health".to_string()),
+            &class_name,
+            "Health",
+        );
+        assert_eq!(route.path, "/health");
+
+        let route = parse_route(
+            &Some("api/[controller]/[action]".to_string()),
+            &None,
+            &class_name,
+            "Search",
+        );
+        assert_eq!(route.path, "/api/Test/Search");
+
+        let route = parse_route(
+            &Some("api/[Controller]".to_string()),
+            &Some("[ACTION]/items/{id}".to_string()),
+            &class_name,
+            "Search",
+        );
+        assert_eq!(route.path, "/api/Test/Search/items/{id}");
+    }
+
+    #[test]
+    fn parses_parameter_constraints() {
+        let parameters = get_route_parameters(
+            "items/{id:int:min(1)}/{page?}/{sort=name}/{*path}/{code:regex(^a:b$)}",
+        );
+
+        assert_eq!(parameters.len(), 5);
+
+        assert_eq!(parameters[0].name, "id");
+        assert_eq!(parameters[0].constraints, vec!["int", "min(1)"]);
+        assert!(!parameters[0].optional);
+
+        assert_eq!(parameters[1].name, "page");
+        assert!(parameters[1].optional);
+
+        assert_eq!(parameters[2].name, "sort");
+        assert_eq!(parameters[2].default_value, Some("name".to_string()));
+
+        assert_eq!(parameters[3].name, "path");
+        assert!(parameters[3].catch_all);
+
+        assert_eq!(parameters[4].name, "code");
+        assert_eq!(parameters[4].constraints, vec!["regex(^a:b$)"]);
+    }
+}
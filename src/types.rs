@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+
+use crate::{TypeMap, TypeOverride};
+
+/// The C# types every project shares, with their TypeScript type and zod
+/// schema.
+const BUILT_IN_TYPES: &[(&str, &str, &str)] = &[
+    ("string", "string", "z.string()"),
+    ("String", "string", "z.string()"),
+    ("char", "string", "z.string()"),
+    ("Guid", "string", "z.string().uuid()"),
+    ("int", "number", "z.number().int()"),
+    ("long", "number", "z.number().int()"),
+    ("short", "number", "z.number().int()"),
+    ("byte", "number", "z.number().int()"),
+    ("sbyte", "number", "z.number().int()"),
+    ("uint", "number", "z.number().int()"),
+    ("ulong", "number", "z.number().int()"),
+    ("ushort", "number", "z.number().int()"),
+    ("Int32", "number", "z.number().int()"),
+    ("Int64", "number", "z.number().int()"),
+    ("float", "number", "z.number()"),
+    ("double", "number", "z.number()"),
+    ("decimal", "number", "z.number()"),
+    ("bool", "boolean", "z.boolean()"),
+    ("Boolean", "boolean", "z.boolean()"),
+    ("DateTime", "Date", "z.coerce.date()"),
+    ("DateTimeOffset", "Date", "z.coerce.date()"),
+    ("DateOnly", "Date", "z.coerce.date()"),
+    ("TimeSpan", "string", "z.string()"),
+    ("object", "unknown", "z.unknown()"),
+    ("dynamic", "unknown", "z.unknown()"),
+    ("void", "void", "z.void()"),
+    ("Task", "void", "z.void()"),
+    ("IActionResult", "unknown", "z.unknown()"),
+    ("ActionResult", "unknown", "z.unknown()"),
+    ("IFormFile", "File", "z.custom<File>()"),
+];
+
+/// Generic types that hold a list of their argument.
+const COLLECTION_TYPES: &[&str] = &[
+    "List",
+    "IList",
+    "IEnumerable",
+    "ICollection",
+    "IReadOnlyList",
+    "IReadOnlyCollection",
+    "HashSet",
+    "ISet",
+];
+
+const DICTIONARY_TYPES: &[&str] = &["Dictionary", "IDictionary", "IReadOnlyDictionary"];
+
+/// Generic types that only wrap the value a method returns.
+const WRAPPER_TYPES: &[&str] = &["Task", "ValueTask", "ActionResult"];
+
+/// Reads the overrides of a type map file, each entry maps a C# type to either
+/// a TypeScript type or a `ts`/`zod` pair:
+///
+/// ```yaml
+/// Money: string
+/// UserId:
+///   ts: string
+///   zod: z.string().uuid()
+/// ```
+pub fn read_type_map(source: &str) -> Result<TypeMap, String> {
+    let overrides = serde_yaml::from_str::<Option<HashMap<String, TypeOverride>>>(source)
+        .map_err(|error| format!("invalid type map: {}", error))?;
+
+    Ok(TypeMap {
+        overrides: overrides.unwrap_or_default(),
+    })
+}
+
+/// The TypeScript type of a C# type, types nothing maps are DTOs and keep
+/// their name.
+pub fn ts_type(types: &TypeMap, cs_type: &str) -> String {
+    let cs_type = simple_name(cs_type);
+
+    if let Some(type_override) = types.overrides.get(cs_type) {
+        return override_ts_type(type_override).to_string();
+    }
+
+    if let Some(inner) = cs_type.strip_suffix('?') {
+        return format!("{} | null", ts_type(types, inner));
+    }
+
+    if let Some(inner) = cs_type.strip_suffix("[]") {
+        return array_type(types, inner);
+    }
+
+    if let Some((name, arguments)) = generic_type(cs_type) {
+        return match (name, arguments.as_slice()) {
+            ("Nullable", [inner]) => format!("{} | null", ts_type(types, inner)),
+            (name, [inner]) if COLLECTION_TYPES.contains(&name) => array_type(types, inner),
+            (name, [inner]) if WRAPPER_TYPES.contains(&name) => ts_type(types, inner),
+            (name, [key, value]) if DICTIONARY_TYPES.contains(&name) => {
+                format!("Record<{}, {}>", ts_type(types, key), ts_type(types, value))
+            }
+            _ => cs_type.to_string(),
+        };
+    }
+
+    match built_in_type(cs_type) {
+        Some((_, ts_type, _)) => ts_type.to_string(),
+        None => cs_type.to_string(),
+    }
+}
+
+/// The zod schema of a C# type, types nothing maps are DTOs and refer to the
+/// `<Type>Schema` written for them.
+pub fn zod_type(types: &TypeMap, cs_type: &str) -> String {
+    let cs_type = simple_name(cs_type);
+
+    if let Some(type_override) = types.overrides.get(cs_type) {
+        return match type_override {
+            TypeOverride::Full { zod: Some(zod), .. } => zod.clone(),
+            type_override => ts_zod_type(override_ts_type(type_override)),
+        };
+    }
+
+    if let Some(inner) = cs_type.strip_suffix('?') {
+        return format!("{}.nullable()", zod_type(types, inner));
+    }
+
+    if let Some(inner) = cs_type.strip_suffix("[]") {
+        return format!("z.array({})", zod_type(types, inner));
+    }
+
+    if let Some((name, arguments)) = generic_type(cs_type) {
+        return match (name, arguments.as_slice()) {
+            ("Nullable", [inner]) => format!("{}.nullable()", zod_type(types, inner)),
+            (name, [inner]) if COLLECTION_TYPES.contains(&name) => {
+                format!("z.array({})", zod_type(types, inner))
+            }
+            (name, [inner]) if WRAPPER_TYPES.contains(&name) => zod_type(types, inner),
+            (name, [key, value]) if DICTIONARY_TYPES.contains(&name) => format!(
+                "z.record({}, {})",
+                zod_type(types, key),
+                zod_type(types, value)
+            ),
+            _ => format!("z.custom<{}>()", cs_type),
+        };
+    }
+
+    match built_in_type(cs_type) {
+        Some((_, _, zod_type)) => zod_type.to_string(),
+        None => format!("{}Schema", cs_type),
+    }
+}
+
+fn built_in_type(cs_type: &str) -> Option<&'static (&'static str, &'static str, &'static str)> {
+    BUILT_IN_TYPES.iter().find(|(name, _, _)| *name == cs_type)
+}
+
+fn override_ts_type(type_override: &TypeOverride) -> &str {
+    match type_override {
+        TypeOverride::Ts(ts) => ts,
+        TypeOverride::Full { ts, .. } => ts,
+    }
+}
+
+/// The schema of an override that only names its TypeScript type.
+fn ts_zod_type(ts_type: &str) -> String {
+    match ts_type {
+        "string" => "z.string()".to_string(),
+        "number" => "z.number()".to_string(),
+        "boolean" => "z.boolean()".to_string(),
+        "Date" => "z.coerce.date()".to_string(),
+        "unknown" => "z.unknown()".to_string(),
+        _ => format!("z.custom<{}>()", ts_type),
+    }
+}
+
+fn array_type(types: &TypeMap, cs_type: &str) -> String {
+    let element = ts_type(types, cs_type);
+
+    if element.contains(' ') {
+        format!("({})[]", element)
+    } else {
+        format!("{}[]", element)
+    }
+}
+
+/// `System.Guid` is the same type as `Guid`, the namespace in front of a type
+/// name is dropped.
+fn simple_name(cs_type: &str) -> &str {
+    let cs_type = cs_type.trim();
+    let name_end = cs_type.find('<').unwrap_or(cs_type.len());
+
+    match cs_type[..name_end].rfind('.') {
+        Some(dot) => &cs_type[dot + 1..],
+        None => cs_type,
+    }
+}
+
+/// Splits `Dictionary<string, List<int>>` into `Dictionary` and its type
+/// arguments.
+fn generic_type(cs_type: &str) -> Option<(&str, Vec<&str>)> {
+    let open = cs_type.find('<')?;
+    let inner = cs_type[open + 1..].strip_suffix('>')?;
+
+    let mut arguments = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (index, char) in inner.char_indices() {
+        match char {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            ',' if depth == 0 => {
+                arguments.push(inner[start..index].trim());
+                start = index + 1;
+            }
+            _ => (),
+        }
+    }
+    arguments.push(inner[start..].trim());
+
+    Some((cs_type[..open].trim(), arguments))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_type_map, ts_type, zod_type};
+    use crate::TypeMap;
+
+    #[test]
+    fn maps_built_in_types() {
+        let types = TypeMap::default();
+
+        assert_eq!(ts_type(&types, "Guid"), "string");
+        assert_eq!(ts_type(&types, "System.DateTime"), "Date");
+        assert_eq!(ts_type(&types, "int?"), "number | null");
+        assert_eq!(ts_type(&types, "Nullable<bool>"), "boolean | null");
+        assert_eq!(ts_type(&types, "int?[]"), "(number | null)[]");
+        assert_eq!(ts_type(&types, "IEnumerable<UserTask>"), "UserTask[]");
+        assert_eq!(
+            ts_type(&types, "Dictionary<string, List<int>>"),
+            "Record<string, number[]>"
+        );
+        assert_eq!(ts_type(&types, "Task<ActionResult<UserTask>>"), "UserTask");
+        assert_eq!(ts_type(&types, "Task"), "void");
+
+        assert_eq!(zod_type(&types, "Guid"), "z.string().uuid()");
+        assert_eq!(zod_type(&types, "int?"), "z.number().int().nullable()");
+        assert_eq!(zod_type(&types, "List<string>"), "z.array(z.string())");
+        assert_eq!(
+            zod_type(&types, "Dictionary<string, List<UserTaskToAdd>>"),
+            "z.record(z.string(), z.array(UserTaskToAddSchema))"
+        );
+        assert_eq!(zod_type(&types, "Task<UserTask>"), "UserTaskSchema");
+    }
+
+    #[test]
+    fn applies_overrides() {
+        let types = read_type_map(concat!(
+            "Money: string\n",
+            "UserId:\n",
+            "  ts: string\n",
+            "  zod: z.string().uuid()\n",
+            "Guid: number\n",
+        ))
+        .unwrap();
+
+        assert_eq!(ts_type(&types, "Money"), "string");
+        assert_eq!(ts_type(&types, "List<Money?>"), "(string | null)[]");
+        assert_eq!(zod_type(&types, "Money"), "z.string()");
+        assert_eq!(zod_type(&types, "UserId"), "z.string().uuid()");
+        assert_eq!(ts_type(&types, "Guid"), "number");
+
+        assert_eq!(read_type_map("").unwrap(), TypeMap::default());
+        assert!(read_type_map("Money: [1, 2]").is_err());
+    }
+}
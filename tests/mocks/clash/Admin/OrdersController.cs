@@ -0,0 +1,14 @@
+using Microsoft.AspNetCore.Mvc;
+
+namespace Admin.Controllers
+{
+    [Route("admin/[controller]")]
+    [ApiController]
+    public class OrdersController : ControllerBase
+    {
+        [HttpDelete("{id}")]
+        public void DeleteOrder(int id)
+        {
+        }
+    }
+}
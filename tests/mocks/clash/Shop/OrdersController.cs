@@ -0,0 +1,15 @@
+using Microsoft.AspNetCore.Mvc;
+
+namespace Shop.Controllers
+{
+    [Route("api/[controller]")]
+    [ApiController]
+    public class OrdersController : ControllerBase
+    {
+        [HttpGet("{id}")]
+        public string GetOrder(int id)
+        {
+            return "order";
+        }
+    }
+}
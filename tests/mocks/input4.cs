@@ -0,0 +1,75 @@
+using System;
+using System.Collections.Generic;
+using System.Linq;
+
+namespace TestApi.Controllers
+{
+    [Route("api/[controller]")]
+    [ApiController]
+    public class SearchController : ControllerBase
+    {
+        private readonly IDbFactory _dbFactory;
+
+        public SearchController(IDbFactory dbFactory)
+        {
+            _dbFactory = dbFactory;
+        }
+
+        [HttpGet("tasks")]
+        public List<UserTask> SearchTasks(string search, int minOrder)
+        {
+            using (var cx = _dbFactory.CreateContext())
+            {
+                return cx.UserTasks.Where(t => (t.Name.Contains(search) || t.Description.StartsWith(search)) && t.OrderNumber >= minOrder && t.CompleteDate == null).ToList();
+            }
+        }
+
+        [HttpGet("open")]
+        public List<UserTask> GetOpenTasks(List<Guid> userOids)
+        {
+            using (var cx = _dbFactory.CreateContext())
+            {
+                return cx.UserTasks.Where(t => userOids.Contains(t.UserOid) && !t.IsDeleted && 3 != t.TaskStatusId).ToList();
+            }
+        }
+
+        [HttpGet("late/{userOid}")]
+        public List<UserTaskDetails> GetLateTasks(Guid userOid)
+        {
+            using (var cx = _dbFactory.CreateContext())
+            {
+                var now = DateTime.UtcNow;
+                return (from ut in cx.UserTasks
+                        join u in cx.Users on ut.UserOid equals u.UserOid
+                        where ut.UserOid == userOid && (ut.DueDate < now || u.Email.EndsWith("@example.com"))
+                        select new UserTaskDetails()
+                        {
+                            UserTaskOid = ut.UserTaskOid,
+                            Name = ut.Name,
+                            Email = u.Email,
+                        }).ToList();
+            }
+        }
+
+        [HttpGet("range")]
+        public List<UserTask> GetTasksInRange(int min)
+        {
+            using (var cx = _dbFactory.CreateContext())
+            {
+                return cx.UserTasks.Where(t => min < t.OrderNumber && t.OrderNumber <= 10 && t.Name != null && t.Name != "").ToList();
+            }
+        }
+
+        [HttpGet]
+        public List<UserTask> GetFlaggedTasks()
+        {
+            using (var cx = _dbFactory.CreateContext())
+            {
+                var flagged = from t in cx.UserTasks
+                              where IsFlagged(t)
+                              select t;
+                return flagged.ToList();
+            }
+        }
+    }
+}
@@ -0,0 +1,118 @@
+using System;
+using System.Collections.Generic;
+using System.Linq;
+
+namespace TestApi.Controllers
+{
+    [Route("api/[controller]")]
+    [ApiController]
+    public class ReportController : ControllerBase
+    {
+        private readonly IDbFactory _dbFactory;
+
+        public ReportController(IDbFactory dbFactory)
+        {
+            _dbFactory = dbFactory;
+        }
+
+        [HttpGet("tasks/{userOid}")]
+        public List<TaskReport> GetTaskReport(Guid userOid)
+        {
+            using (var cx = _dbFactory.CreateContext())
+            {
+                return (from ut in cx.UserTasks
+                        join u in cx.Users on ut.UserOid equals u.UserOid
+                        join c in cx.Companies on u.CompanyOid equals c.CompanyOid
+                        join uts in cx.TaskStatuses on ut.TaskStatusId equals uts.TaskStatusId into statuses
+                        from s in statuses.DefaultIfEmpty()
+                        where ut.UserOid == userOid && c.IsActive == true
+                        select new TaskReport()
+                        {
+                            UserTaskOid = ut.UserTaskOid,
+                            Name = ut.Name,
+                            OrderNumber = ut.OrderNumber,
+                            Email = u.Email,
+                            Company = c.Name,
+                            Status = s.Name,
+                        }).ToList();
+            }
+        }
+
+        [HttpGet("open/{userOid}")]
+        public List<TaskSummary> GetOpenTasks(Guid userOid, int page)
+        {
+            using (var cx = _dbFactory.CreateContext())
+            {
+                return cx.UserTasks
+                    .Include(t => t.User)
+                    .Where(t => t.UserOid == userOid)
+                    .Where(t => t.CompleteDate == null)
+                    .OrderByDescending(t => t.DueDate)
+                    .ThenBy(t => t.Name)
+                    .Skip(page * 20)
+                    .Take(20)
+                    .Select(t => new TaskSummary
+                    {
+                        UserTaskOid = t.UserTaskOid,
+                        Name = t.Name,
+                    })
+                    .ToList();
+            }
+        }
+
+        [HttpGet("late/{userOid}")]
+        public int CountLateTasks(Guid userOid)
+        {
+            using (var cx = _dbFactory.CreateContext())
+            {
+                return cx.UserTasks.Count(t => t.UserOid == userOid && t.DueDate < DateTime.UtcNow);
+            }
+        }
+
+        [HttpGet("hours/{userOid}")]
+        public decimal GetTotalHours(Guid userOid)
+        {
+            using (var cx = _dbFactory.CreateContext())
+            {
+                var hasTasks = cx.UserTasks.Any(t => t.UserOid == userOid);
+
+                return cx.UserTasks.Where(t => t.UserOid == userOid).Sum(t => t.Hours);
+            }
+        }
+
+        [HttpGet("next/{userOid}")]
+        public UserTaskDetails GetNextTask(Guid userOid)
+        {
+            using (var cx = _dbFactory.CreateContext())
+            {
+                return (from ut in cx.UserTasks
+                        where ut.UserOid == userOid
+                        orderby ut.DueDate descending, ut.Name
+                        select new UserTaskDetails()
+                        {
+                            UserTaskOid = ut.UserTaskOid,
+                            Name = ut.Name,
+                        }).First();
+            }
+        }
+
+        [HttpGet("summaries/{userOid}")]
+        public List<TaskSummary> GetTaskSummaries(Guid userOid)
+        {
+            using (var cx = _dbFactory.CreateContext())
+            {
+                return cx.UserTasks
+                    .Where(t => t.UserOid == userOid)
+                    .Select(t => new TaskSummary
+                    {
+                        t.UserTaskOid,
+                        Status = new { t.TaskStatus.Name, t.TaskStatusId },
+                        Owner = t.User.Name,
+                        Hours = t.Hours ?? 0,
+                        Comments = t.Comments.OrderBy(c => c.CreatedAt).Select(c => new CommentDto { Text = c.Text }).ToList(),
+                    })
+                    .ToList();
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+using System;
+using Microsoft.AspNetCore.Mvc;
+
+namespace Tasks.Api
+{
+    public class TaskFilter
+    {
+        public Guid UserOid { get; set; }
+        public int Page { get; set; } = 1;
+
+        public TaskFilter(Guid userOid)
+        {
+            UserOid = userOid;
+        }
+
+        public class Range
+        {
+            public int From { get; set; }
+            public int To { get; set; }
+        }
+    }
+
+    [Route("api/[controller]")]
+    [ApiController]
+    public class TasksController : ControllerBase, IDisposable
+    {
+        private readonly IDbContextFactory<TaskContext> _dbFactory;
+
+        public TasksController(IDbContextFactory<TaskContext> dbFactory)
+        {
+            _dbFactory = dbFactory;
+        }
+
+        [HttpGet("{userOid}")]
+        public int CountTasks(Guid userOid)
+        {
+            using (var cx = _dbFactory.CreateContext())
+            {
+                return cx.UserTasks.Count(t => t.UserOid == userOid);
+            }
+        }
+
+        public void Dispose() => _dbFactory.Dispose();
+    }
+}
+
+namespace Tasks.Admin
+{
+    [Route("admin/[controller]")]
+    public class UsersController : ControllerBase
+    {
+        public UsersController()
+        {
+        }
+
+        [HttpDelete("{userOid}")]
+        public void DeleteUser(Guid userOid)
+        {
+        }
+    }
+}
@@ -0,0 +1,29 @@
+using Microsoft.AspNetCore.Mvc;
+
+namespace Test.Controllers;
+
+public record UserDto(Guid Id, string Name);
+
+public readonly record struct TaskKey(Guid UserOid, int OrderNumber);
+
+public record TaskDto(Guid UserTaskOid, [property: JsonPropertyName("name")] string Name)
+{
+    public List<string> Tags { get; init; } = new();
+}
+
+[Route("api/[controller]")]
+[ApiController]
+public class TestController(IDbContextFactory<TaskContext> dbFactory) : ControllerBase
+{
+    [HttpGet("{userOid}")]
+    public List<UserDto> GetUsers(Guid userOid)
+    {
+        using (var cx = dbFactory.CreateContext())
+        {
+            return cx.Users
+                .Where(u => u.UserOid == userOid)
+                .Select(u => new UserDto(u.UserOid, u.Name))
+                .ToList();
+        }
+    }
+}
@@ -0,0 +1,31 @@
+using System;
+using System.Collections.Generic;
+
+namespace Test.Models
+{
+    public enum Status
+    {
+        Open,
+        Done = 3
+    }
+
+    public class User
+    {
+        public Guid UserOid { get; set; }
+        public List<UserTask> Tasks { get; set; }
+    }
+
+    public class UserTask
+    {
+        public Guid UserTaskOid { get; set; }
+        public User User { get; set; }
+        public Status Status { get; set; }
+    }
+
+    public class Node
+    {
+        public string Name { get; set; }
+        public Node? Parent { get; set; }
+        public List<Node> Children { get; set; }
+    }
+}
@@ -0,0 +1,46 @@
+using System;
+using System.Collections.Generic;
+using System.ComponentModel.DataAnnotations;
+
+namespace Test.Models
+{
+    public class AdminName
+    {
+        [Required]
+        public string UserName { get; set; }
+        public string LoginEmail { get; set; }
+        public string Password { get; set; }
+    }
+
+    public class UserTaskToAdd
+    {
+        public Guid UserTaskOid { get; set; }
+        public Guid UserOid { get; set; }
+        public string TaskName { get; set; } = "";
+        public int? OrderNumber { get; set; }
+        public DateTime? DueDate { get; init; }
+        public List<string> Tags { get; set; } = new List<string> { "new" };
+
+        private int _version;
+        public int Version => _version;
+        internal string Secret { get; set; }
+
+        public bool IsDue(DateTime now)
+        {
+            return DueDate < now;
+        }
+    }
+
+    public record UserTaskDetails
+    {
+        public Guid UserTaskOid { get; init; }
+        public string Name { get; init; }
+        public List<UserTaskToAdd> SubTasks { get; init; }
+        public Dictionary<string, int> Counts { get; init; }
+
+        public class Comment
+        {
+            public string Text { get; set; }
+        }
+    }
+}
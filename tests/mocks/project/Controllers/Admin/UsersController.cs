@@ -0,0 +1,17 @@
+using Microsoft.AspNetCore.Mvc;
+
+namespace Shop.Controllers.Admin
+{
+    [Route("api/admin/[controller]")]
+    [ApiController]
+    public class UsersController : ControllerBase
+    {
+        [HttpDelete("{id}")]
+        public void DeleteUser(int id)
+        {
+            if (id > 0)
+            {
+                return;
+        }
+    }
+}
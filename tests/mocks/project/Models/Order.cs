@@ -0,0 +1,7 @@
+namespace Shop.Models
+{
+    public class Order
+    {
+        public int Id { get; set; }
+    }
+}
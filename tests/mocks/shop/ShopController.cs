@@ -0,0 +1,34 @@
+using Microsoft.AspNetCore.Mvc;
+
+namespace Shop.Controllers
+{
+    [Route("api/[controller]")]
+    [ApiController]
+    public class UsersController : ControllerBase
+    {
+        [HttpGet("{id}")]
+        public string Get(int id)
+        {
+            return "user";
+        }
+    }
+
+    [Route("api/[controller]")]
+    [ApiController]
+    public class OrdersController : ControllerBase
+    {
+        [HttpGet("{id}")]
+        public string Get(int id)
+        {
+            return "order";
+        }
+    }
+
+    public class Worker
+    {
+        public void Run()
+        {
+            Console.WriteLine("run");
+        }
+    }
+}